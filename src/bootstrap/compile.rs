@@ -106,6 +106,10 @@ pub fn std_link(build: &Build,
     if target.contains("musl") && !target.contains("mips") {
         copy_musl_third_party_objects(build, &libdir);
     }
+
+    if build.build_sanitizers(target) {
+        copy_sanitizers(build, target, &libdir);
+    }
 }
 
 /// Copies the crt(1,i,n).o startup objects
@@ -117,6 +121,22 @@ fn copy_musl_third_party_objects(build: &Build, into: &Path) {
     }
 }
 
+/// Copies the compiler-rt sanitizer and profiling runtimes into the sysroot
+///
+/// They're renamed to `librustc_{asan,lsan,msan,tsan,profiler}.a`, which is
+/// what the compiler passes to the linker for `-Z sanitizer` and
+/// `-C profile-generate`.
+fn copy_sanitizers(build: &Build, target: &str, into: &Path) {
+    let arch = target.split('-').next().unwrap();
+    let src = build.sanitizers_out(target).join("build/lib/linux");
+    copy(&src.join(format!("libclang_rt.profile-{}.a", arch)),
+         &into.join("librustc_profiler.a"));
+    for &runtime in &["asan", "lsan", "msan", "tsan"] {
+        let name = format!("libclang_rt.{}-{}.a", runtime, arch);
+        copy(&src.join(name), &into.join(format!("librustc_{}.a", runtime)));
+    }
+}
+
 /// Build and prepare startup objects like rsbegin.o and rsend.o
 ///
 /// These are primarily used on Windows right now for linking executables/dlls.
@@ -43,6 +43,8 @@ pub struct Config {
     pub submodules: bool,
     pub compiler_docs: bool,
     pub docs: bool,
+    pub sanitizers: bool,
+    pub profiler: bool,
     pub target_config: HashMap<String, Target>,
 
     // llvm codegen options
@@ -117,6 +119,8 @@ struct Build {
     rustc: Option<String>,
     compiler_docs: Option<bool>,
     docs: Option<bool>,
+    sanitizers: Option<bool>,
+    profiler: Option<bool>,
 }
 
 /// TOML representation of how the LLVM build is configured.
@@ -221,6 +225,8 @@ impl Config {
         config.cargo = build.cargo.map(PathBuf::from);
         set(&mut config.compiler_docs, build.compiler_docs);
         set(&mut config.docs, build.docs);
+        set(&mut config.sanitizers, build.sanitizers);
+        set(&mut config.profiler, build.profiler);
 
         if let Some(ref llvm) = toml.llvm {
             set(&mut config.ccache, llvm.ccache);
@@ -315,6 +321,8 @@ impl Config {
                 ("MANAGE_SUBMODULES", self.submodules),
                 ("COMPILER_DOCS", self.compiler_docs),
                 ("DOCS", self.docs),
+                ("SANITIZERS", self.sanitizers),
+                ("PROFILER", self.profiler),
                 ("LLVM_ASSERTIONS", self.llvm_assertions),
                 ("OPTIMIZE_LLVM", self.llvm_optimize),
                 ("LLVM_VERSION_CHECK", self.llvm_version_check),
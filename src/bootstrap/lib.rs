@@ -259,6 +259,9 @@ impl Build {
                 TestHelpers { _dummy } => {
                     native::test_helpers(self, target.target);
                 }
+                Sanitizers { _dummy } => {
+                    native::sanitizers(self, target.target);
+                }
                 Libstd { compiler } => {
                     compile::std(self, target.target, &compiler);
                 }
@@ -848,6 +851,19 @@ impl Build {
         self.out.join(target).join("rust-test-helpers")
     }
 
+    /// Root output directory for the compiler-rt sanitizer runtimes compiled
+    /// for `target`
+    fn sanitizers_out(&self, target: &str) -> PathBuf {
+        self.out.join(target).join("sanitizers")
+    }
+
+    /// Returns whether the compiler-rt sanitizer and profiling runtimes are
+    /// built for `target`.
+    fn build_sanitizers(&self, target: &str) -> bool {
+        (self.config.sanitizers || self.config.profiler) &&
+            target == "x86_64-unknown-linux-gnu"
+    }
+
     /// Adds the compiler's directory of dynamic libraries to `cmd`'s dynamic
     /// library lookup path.
     fn add_rustc_lib_path(&self, compiler: &Compiler, cmd: &mut Command) {
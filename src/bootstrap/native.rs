@@ -152,3 +152,44 @@ pub fn test_helpers(build: &Build, target: &str) {
        .file(build.src.join("src/rt/rust_test_helpers.c"))
        .compile("librust_test_helpers.a");
 }
+
+/// Compiles the sanitizer and profiling runtimes from compiler-rt, which are
+/// linked into executables built with `-Z sanitizer` or `-C profile-generate`.
+pub fn sanitizers(build: &Build, target: &str) {
+    if !build.build_sanitizers(target) {
+        return
+    }
+
+    let dst = build.sanitizers_out(target);
+    let done_stamp = dst.join("sanitizers-finished-building");
+    if fs::metadata(&done_stamp).is_ok() {
+        return
+    }
+
+    println!("Building sanitizers for {}", target);
+
+    let _ = fs::remove_dir_all(&dst.join("build"));
+    t!(fs::create_dir_all(&dst.join("build")));
+
+    // http://compiler-rt.llvm.org/
+    let mut cfg = cmake::Config::new(build.src.join("src/compiler-rt"));
+    if build.config.ninja {
+        cfg.generator("Ninja");
+    }
+    cfg.target(target)
+       .host(&build.config.build)
+       .out_dir(&dst)
+       .profile("Release")
+       .define("LLVM_CONFIG_PATH", build.llvm_config(&build.config.build))
+       .define("COMPILER_RT_DEFAULT_TARGET_TRIPLE", target)
+       .define("COMPILER_RT_BUILD_SANITIZERS", "ON")
+       .define("COMPILER_RT_BUILD_BUILTINS", "OFF")
+       .define("COMPILER_RT_BUILD_XRAY", "OFF")
+       .define("COMPILER_RT_INCLUDE_TESTS", "OFF")
+       .define("CMAKE_C_COMPILER", build.cc(target))
+       .define("CMAKE_CXX_COMPILER", build.cxx(target))
+       .build_arg("-j").build_arg(build.jobs().to_string());
+    cfg.build();
+
+    t!(File::create(&done_stamp));
+}
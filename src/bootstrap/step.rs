@@ -83,6 +83,7 @@ macro_rules! targets {
             // with braces are unstable so we just pick something that works.
             (llvm, Llvm { _dummy: () }),
             (test_helpers, TestHelpers { _dummy: () }),
+            (sanitizers, Sanitizers { _dummy: () }),
             (debugger_scripts, DebuggerScripts { stage: u32 }),
 
             // Steps for various pieces of documentation that we can generate,
@@ -336,7 +337,8 @@ impl<'a> Step<'a> {
                 vec![self.libstd(compiler)]
             }
             Source::Libstd { compiler } => {
-                vec![self.rustc(compiler.stage).target(compiler.host)]
+                vec![self.rustc(compiler.stage).target(compiler.host),
+                     self.sanitizers(())]
             }
             Source::LibrustcLink { compiler, host } => {
                 vec![self.librustc(compiler),
@@ -351,6 +353,9 @@ impl<'a> Step<'a> {
             }
             Source::Llvm { _dummy } => Vec::new(),
             Source::TestHelpers { _dummy } => Vec::new(),
+            Source::Sanitizers { _dummy } => {
+                vec![self.target(&build.config.build).llvm(())]
+            }
             Source::DebuggerScripts { stage: _ } => Vec::new(),
 
             // Note that all doc targets depend on artifacts from the build
@@ -127,6 +127,7 @@ pub mod util {
     pub mod nodemap;
     pub mod num;
     pub mod fs;
+    pub mod profiling;
 }
 
 // A private module so that macro-expanded idents like
@@ -198,6 +198,30 @@ declare_lint! {
     "safe access to extern statics was erroneously allowed"
 }
 
+declare_lint! {
+    pub UNRESOLVED_DOC_LINKS,
+    Warn,
+    "detects intra-doc links that rustdoc is unable to resolve"
+}
+
+declare_lint! {
+    pub MISSING_ITEM_DOCS,
+    Allow,
+    "detects public items without documentation in documented modules"
+}
+
+declare_lint! {
+    pub UNKNOWN_CODE_BLOCK_ATTRIBUTES,
+    Warn,
+    "detects code block attributes that look like misspelled rustdoc attributes"
+}
+
+declare_lint! {
+    pub EMPTY_DOCS,
+    Warn,
+    "detects empty doc comments"
+}
+
 /// Does nothing as a lint pass, but registers some `Lint`s
 /// which are used by other parts of the compiler.
 #[derive(Copy, Clone)]
@@ -235,7 +259,11 @@ impl LintPass for HardwiredLints {
             SUPER_OR_SELF_IN_GLOBAL_PATH,
             HR_LIFETIME_IN_ASSOC_TYPE,
             LIFETIME_UNDERSCORE,
-            SAFE_EXTERN_STATICS
+            SAFE_EXTERN_STATICS,
+            UNRESOLVED_DOC_LINKS,
+            MISSING_ITEM_DOCS,
+            UNKNOWN_CODE_BLOCK_ATTRIBUTES,
+            EMPTY_DOCS
         )
     }
 }
@@ -94,6 +94,9 @@ pub fn calculate(sess: &session::Session) {
     for &ty in sess.crate_types.borrow().iter() {
         let linkage = calculate_type(sess, ty);
         verify_ok(sess, &linkage);
+        if sess.opts.output_types.contains_key(&config::OutputType::Exe) {
+            verify_linkable(sess, &linkage);
+        }
         fmts.insert(ty, linkage);
     }
     sess.abort_if_errors();
@@ -319,6 +322,27 @@ fn activate_injected_dep(injected: Option<CrateNum>,
 
 // After the linkage for a crate has been determined we need to verify that
 // there's only going to be one allocator in the output.
+/// Checks that none of the crates linked statically is a metadata-only rlib
+/// written by `--emit=metadata`, which has no code to link against.
+fn verify_linkable(sess: &session::Session, list: &[Linkage]) {
+    for (i, kind) in list.iter().enumerate() {
+        if *kind != Linkage::Static {
+            continue
+        }
+        let cnum = CrateNum::new(i + 1);
+        if let Some((ref path, _)) = sess.cstore.used_crate_source(cnum).rlib {
+            if path.extension().map_or(false, |ext| ext == "rmeta") {
+                sess.struct_err(&format!("crate `{}` required to be available in rlib, \
+                                          but only its metadata was found at {}",
+                                         sess.cstore.crate_name(cnum), path.display()))
+                    .help("crates built with `--emit=metadata` can only be used for \
+                           type-checking; build an rlib with `--emit=link` to link against")
+                    .emit();
+            }
+        }
+    }
+}
+
 fn verify_ok(sess: &session::Session, list: &[Linkage]) {
     if list.len() == 0 {
         return
@@ -40,7 +40,7 @@ use std::collections::btree_map::Values as BTreeMapValuesIter;
 use std::fmt;
 use std::hash::{Hasher, SipHasher};
 use std::iter::FromIterator;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub struct Config {
     pub target: Target,
@@ -72,6 +72,7 @@ pub enum OutputType {
     Assembly,
     LlvmAssembly,
     Object,
+    Metadata,
     Exe,
     DepInfo,
 }
@@ -79,7 +80,10 @@ pub enum OutputType {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ErrorOutputType {
     HumanReadable(ColorConfig),
+    /// One line per message, for editors and other line-based tools.
+    Short(ColorConfig),
     Json,
+    Sarif,
 }
 
 impl Default for ErrorOutputType {
@@ -92,6 +96,7 @@ impl OutputType {
     fn is_compatible_with_codegen_units_and_single_output_file(&self) -> bool {
         match *self {
             OutputType::Exe |
+            OutputType::Metadata |
             OutputType::DepInfo => true,
             OutputType::Bitcode |
             OutputType::Assembly |
@@ -106,6 +111,7 @@ impl OutputType {
             OutputType::Assembly => "asm",
             OutputType::LlvmAssembly => "llvm-ir",
             OutputType::Object => "obj",
+            OutputType::Metadata => "metadata",
             OutputType::Exe => "link",
             OutputType::DepInfo => "dep-info",
         }
@@ -117,6 +123,7 @@ impl OutputType {
             OutputType::Assembly => "s",
             OutputType::LlvmAssembly => "ll",
             OutputType::Object => "o",
+            OutputType::Metadata => "rmeta",
             OutputType::DepInfo => "d",
             OutputType::Exe => "",
         }
@@ -150,6 +157,20 @@ impl OutputTypes {
     pub fn values<'a>(&'a self) -> BTreeMapValuesIter<'a, OutputType, Option<PathBuf>> {
         self.0.values()
     }
+
+    /// True if any of the requested outputs needs the crate to be translated,
+    /// i.e. if there is more to emit than metadata and dep-info.
+    pub fn should_trans(&self) -> bool {
+        self.0.keys().any(|k| match *k {
+            OutputType::Bitcode |
+            OutputType::Assembly |
+            OutputType::LlvmAssembly |
+            OutputType::Object |
+            OutputType::Exe => true,
+            OutputType::Metadata |
+            OutputType::DepInfo => false,
+        })
+    }
 }
 
 
@@ -507,6 +528,25 @@ impl PanicStrategy {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Hash)]
+pub enum Sanitizer {
+    Address,
+    Leak,
+    Memory,
+    Thread,
+}
+
+impl Sanitizer {
+    pub fn desc(&self) -> &'static str {
+        match *self {
+            Sanitizer::Address => "address",
+            Sanitizer::Leak => "leak",
+            Sanitizer::Memory => "memory",
+            Sanitizer::Thread => "thread",
+        }
+    }
+}
+
 /// Declare a macro that will define all CodegenOptions/DebuggingOptions fields and parsers all
 /// at once. The goal of this macro is to define an interface that can be
 /// programmatically used by the option parser in order to initialize the struct
@@ -616,11 +656,14 @@ macro_rules! options {
             Some("a number");
         pub const parse_panic_strategy: Option<&'static str> =
             Some("either `panic` or `abort`");
+        pub const parse_sanitizer: Option<&'static str> =
+            Some("one of: `address`, `leak`, `memory` or `thread`");
     }
 
     #[allow(dead_code)]
     mod $mod_set {
-        use super::{$struct_name, Passes, SomePasses, AllPasses, PanicStrategy};
+        use super::{$struct_name, Passes, SomePasses, AllPasses, PanicStrategy,
+                    Sanitizer};
 
         $(
             pub fn $opt(cg: &mut $struct_name, v: Option<&str>) -> bool {
@@ -740,6 +783,17 @@ macro_rules! options {
             }
             true
         }
+
+        fn parse_sanitizer(slot: &mut Option<Sanitizer>, v: Option<&str>) -> bool {
+            match v {
+                Some("address") => *slot = Some(Sanitizer::Address),
+                Some("leak") => *slot = Some(Sanitizer::Leak),
+                Some("memory") => *slot = Some(Sanitizer::Memory),
+                Some("thread") => *slot = Some(Sanitizer::Thread),
+                _ => return false
+            }
+            true
+        }
     }
 ) }
 
@@ -811,6 +865,11 @@ options! {CodegenOptions, CodegenSetter, basic_codegen_options,
         "set the inlining threshold for"),
     panic: PanicStrategy = (PanicStrategy::Unwind, parse_panic_strategy,
         [TRACKED], "panic strategy to compile crate with"),
+    profile_generate: Option<String> = (None, parse_opt_string, [TRACKED],
+        "instrument the code for profile-guided optimization, writing profiles \
+         into the given directory"),
+    profile_use: Option<String> = (None, parse_opt_string, [TRACKED],
+        "optimize using the given merged profile (`.profdata` file)"),
 }
 
 options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
@@ -924,6 +983,17 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
           "the directory the MIR is dumped into"),
     perf_stats: bool = (false, parse_bool, [UNTRACKED],
           "print some performance-related statistics"),
+    apply_suggestions: bool = (false, parse_bool, [UNTRACKED],
+          "rewrite source files in place to apply machine-applicable suggestions"),
+    sanitizer: Option<Sanitizer> = (None, parse_sanitizer, [TRACKED],
+          "use a sanitizer"),
+    self_profile: bool = (false, parse_bool, [UNTRACKED],
+          "write a trace of the time spent in each pass, codegen unit and LLVM module, \
+           viewable in Chrome's `about:tracing`"),
+    print_type_sizes: bool = (false, parse_bool, [UNTRACKED_WITH_WARNING(true,
+          "The output of `-Z print-type-sizes` will only include the types of \
+           re-translated modules when used with incremental compilation")],
+          "print the size, alignment and field layout of each type, largest first"),
 }
 
 pub fn default_lib_output() -> CrateType {
@@ -1168,7 +1238,7 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
                "NAME"),
         opt::multi_s("", "emit", "Comma separated list of types of output for \
                               the compiler to emit",
-                 "[asm|llvm-bc|llvm-ir|obj|link|dep-info]"),
+                 "[asm|llvm-bc|llvm-ir|obj|metadata|link|dep-info]"),
         opt::multi_s("", "print", "Comma separated list of compiler information to \
                                print on stdout",
                  "[crate-name|file-names|sysroot|cfg|target-list|target-cpus|\
@@ -1207,7 +1277,7 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
         opt::multi_ubnr("Z", "", "Set internal debugging options", "FLAG"),
         opt::opt_s("", "error-format",
                       "How errors and other messages are produced",
-                      "human|json"),
+                      "human|short|json|sarif"),
         opt::opt_s("", "color", "Configure coloring of output:
                                  auto   = colorize, if output goes to a tty (default);
                                  always = always colorize output;
@@ -1281,14 +1351,16 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
     let error_format = if matches.opts_present(&["error-format".to_owned()]) {
         match matches.opt_str("error-format").as_ref().map(|s| &s[..]) {
             Some("human")   => ErrorOutputType::HumanReadable(color),
+            Some("short") => ErrorOutputType::Short(color),
             Some("json") => ErrorOutputType::Json,
+            Some("sarif") => ErrorOutputType::Sarif,
 
             None => ErrorOutputType::HumanReadable(color),
 
             Some(arg) => {
                 early_error(ErrorOutputType::HumanReadable(color),
-                            &format!("argument for --error-format must be human or json (instead \
-                                      was `{}`)",
+                            &format!("argument for --error-format must be human, short, \
+                                      json or sarif (instead was `{}`)",
                                      arg))
             }
         }
@@ -1333,6 +1405,7 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
                     "llvm-ir" => OutputType::LlvmAssembly,
                     "llvm-bc" => OutputType::Bitcode,
                     "obj" => OutputType::Object,
+                    "metadata" => OutputType::Metadata,
                     "link" => OutputType::Exe,
                     "dep-info" => OutputType::DepInfo,
                     part => {
@@ -1397,6 +1470,25 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
     let sysroot_opt = matches.opt_str("sysroot").map(|m| PathBuf::from(&m));
     let target = matches.opt_str("target").unwrap_or(
         host_triple().to_string());
+    if let Some(sanitizer) = debugging_opts.sanitizer {
+        // The sanitizer runtimes are only built for this target.
+        if target != "x86_64-unknown-linux-gnu" {
+            early_error(error_format, &format!("-Z sanitizer={} is only supported on \
+                                                x86_64-unknown-linux-gnu",
+                                               sanitizer.desc()));
+        }
+    }
+
+    if cg.profile_generate.is_some() && cg.profile_use.is_some() {
+        early_error(error_format, "-C profile-generate and -C profile-use are mutually \
+                                   exclusive");
+    }
+    if let Some(ref path) = cg.profile_use {
+        if !Path::new(path).is_file() {
+            early_error(error_format, &format!("the profile `{}` passed to -C profile-use \
+                                                does not exist", path));
+        }
+    }
     let opt_level = {
         if matches.opt_present("O") {
             if cg.opt_level.is_some() {
@@ -1425,6 +1517,12 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
             }
         }
     };
+    // LLVM only schedules the instrumentation pass as part of its optimization
+    // pipeline, so without optimizations no profile would ever be written.
+    if cg.profile_generate.is_some() && opt_level == OptLevel::No {
+        early_error(error_format, "-C profile-generate requires optimizations to be \
+                                   enabled (`-O` or `-C opt-level`)");
+    }
     let debug_assertions = cg.debug_assertions.unwrap_or(opt_level == OptLevel::No);
     let debuginfo = if matches.opt_present("g") {
         if cg.debuginfo.is_some() {
@@ -1665,7 +1763,7 @@ mod dep_tracking {
     use std::collections::BTreeMap;
     use std::hash::{Hash, SipHasher};
     use std::path::PathBuf;
-    use super::{Passes, PanicStrategy, CrateType, OptLevel, DebugInfoLevel,
+    use super::{Passes, PanicStrategy, Sanitizer, CrateType, OptLevel, DebugInfoLevel,
                 OutputTypes, Externs, ErrorOutputType};
     use syntax::feature_gate::UnstableFeatures;
 
@@ -1708,6 +1806,7 @@ mod dep_tracking {
     impl_dep_tracking_hash_via_hash!(Option<String>);
     impl_dep_tracking_hash_via_hash!(Option<lint::Level>);
     impl_dep_tracking_hash_via_hash!(Option<PathBuf>);
+    impl_dep_tracking_hash_via_hash!(Option<Sanitizer>);
     impl_dep_tracking_hash_via_hash!(CrateType);
     impl_dep_tracking_hash_via_hash!(PanicStrategy);
     impl_dep_tracking_hash_via_hash!(Passes);
@@ -1870,6 +1969,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_emit_metadata_skips_trans() {
+        let matches = getopts(&[
+            "--emit=metadata,dep-info".to_string()
+        ], &optgroups()).unwrap();
+        let (sessopts, _) = build_session_options_and_crate_config(&matches);
+        assert!(sessopts.output_types.contains_key(&OutputType::Metadata));
+        assert!(!sessopts.output_types.should_trans());
+
+        let matches = getopts(&[
+            "--emit=metadata,link".to_string()
+        ], &optgroups()).unwrap();
+        let (sessopts, _) = build_session_options_and_crate_config(&matches);
+        assert!(sessopts.output_types.should_trans());
+    }
+
     #[test]
     fn test_output_types_tracking_hash_different_paths() {
         let mut v1 = super::basic_options();
@@ -2320,6 +2435,14 @@ mod tests {
         opts = reference.clone();
         opts.cg.panic = PanicStrategy::Abort;
         assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.cg.profile_generate = Some(String::from("abc"));
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.cg.profile_use = Some(String::from("abc"));
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
     }
 
     #[test]
@@ -2398,6 +2521,12 @@ mod tests {
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.dump_mir_dir = Some(String::from("abc"));
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.apply_suggestions = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.self_profile = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.print_type_sizes = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
 
         // Make sure changing a [TRACKED] option changes the hash
         opts = reference.clone();
@@ -2443,5 +2572,9 @@ mod tests {
         opts = reference.clone();
         opts.debugging_opts.mir_opt_level = Some(1);
         assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.sanitizer = Some(super::Sanitizer::Address);
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
     }
 }
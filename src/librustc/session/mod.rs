@@ -19,12 +19,14 @@ use session::config::{DebugInfoLevel, PanicStrategy};
 use ty::tls;
 use util::nodemap::{NodeMap, FnvHashMap};
 use util::common::duration_to_secs_str;
+use util::profiling::{self, SelfProfiler};
 use mir::transform as mir_pass;
 
 use syntax::ast::NodeId;
 use errors::{self, DiagnosticBuilder};
 use errors::emitter::{Emitter, EmitterWriter};
 use syntax::json::JsonEmitter;
+use syntax::sarif::SarifEmitter;
 use syntax::feature_gate;
 use syntax::parse;
 use syntax::parse::ParseSess;
@@ -43,6 +45,7 @@ use std::collections::HashMap;
 use std::env;
 use std::ffi::CString;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::fmt;
 use std::time::Duration;
 use libc::c_int;
@@ -106,6 +109,9 @@ pub struct Session {
     /// Some measurements that are being gathered during compilation.
     pub perf_stats: PerfStats,
 
+    /// Records where the time is spent if `-Z self-profile` is given.
+    pub self_profiler: Option<Arc<SelfProfiler>>,
+
     next_node_id: Cell<ast::NodeId>,
 }
 
@@ -247,6 +253,17 @@ impl Session {
     pub fn span_unimpl<S: Into<MultiSpan>>(&self, sp: S, msg: &str) -> ! {
         self.diagnostic().span_unimpl(sp, msg)
     }
+    /// Rewrites the source files with the machine-applicable suggestions
+    /// collected under `-Z apply-suggestions`.
+    pub fn apply_suggestions(&self) {
+        let suggestions = self.diagnostic().take_suggestions();
+        match errors::fixes::apply_suggestions(self.codemap(), &suggestions) {
+            Ok(0) => {}
+            Ok(1) => self.note_without_error("applied 1 suggestion"),
+            Ok(n) => self.note_without_error(&format!("applied {} suggestions", n)),
+            Err(e) => self.err(&format!("failed to apply suggestions: {}", e)),
+        }
+    }
     pub fn unimpl(&self, msg: &str) -> ! {
         self.diagnostic().unimpl(msg)
     }
@@ -475,15 +492,23 @@ pub fn build_session_with_codemap(sopts: config::Options,
             Box::new(EmitterWriter::stderr(color_config,
                                            Some(codemap.clone())))
         }
+        config::ErrorOutputType::Short(color_config) => {
+            Box::new(EmitterWriter::stderr_short(color_config,
+                                                 Some(codemap.clone())))
+        }
         config::ErrorOutputType::Json => {
             Box::new(JsonEmitter::stderr(Some(registry), codemap.clone()))
         }
+        config::ErrorOutputType::Sarif => {
+            Box::new(SarifEmitter::stderr(Some(registry), codemap.clone()))
+        }
     };
 
     let diagnostic_handler =
         errors::Handler::with_emitter(can_print_warnings,
                                       treat_err_as_bug,
                                       emitter);
+    diagnostic_handler.set_collect_suggestions(sopts.debugging_opts.apply_suggestions);
 
     build_session_(sopts,
                    dep_graph,
@@ -522,6 +547,13 @@ pub fn build_session_(sopts: config::Options,
         }
     );
 
+    let self_profiler = if sopts.debugging_opts.self_profile {
+        Some(Arc::new(SelfProfiler::new()))
+    } else {
+        None
+    };
+    profiling::install(self_profiler.clone());
+
     let sess = Session {
         dep_graph: dep_graph.clone(),
         target: target_cfg,
@@ -557,7 +589,8 @@ pub fn build_session_(sopts: config::Options,
             incr_comp_hashes_time: Cell::new(Duration::from_secs(0)),
             incr_comp_hashes_count: Cell::new(0),
             symbol_hash_time: Cell::new(Duration::from_secs(0)),
-        }
+        },
+        self_profiler: self_profiler,
     };
 
     init_llvm(&sess);
@@ -645,7 +678,11 @@ pub fn early_error(output: config::ErrorOutputType, msg: &str) -> ! {
             Box::new(EmitterWriter::stderr(color_config,
                                            None))
         }
+        config::ErrorOutputType::Short(color_config) => {
+            Box::new(EmitterWriter::stderr_short(color_config, None))
+        }
         config::ErrorOutputType::Json => Box::new(JsonEmitter::basic()),
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::basic()),
     };
     let handler = errors::Handler::with_emitter(true, false, emitter);
     handler.emit(&MultiSpan::new(), msg, errors::Level::Fatal);
@@ -658,7 +695,11 @@ pub fn early_warn(output: config::ErrorOutputType, msg: &str) {
             Box::new(EmitterWriter::stderr(color_config,
                                            None))
         }
+        config::ErrorOutputType::Short(color_config) => {
+            Box::new(EmitterWriter::stderr_short(color_config, None))
+        }
         config::ErrorOutputType::Json => Box::new(JsonEmitter::basic()),
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::basic()),
     };
     let handler = errors::Handler::with_emitter(true, false, emitter);
     handler.emit(&MultiSpan::new(), msg, errors::Level::Warning);
@@ -0,0 +1,117 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Self-profiling of the compiler, enabled with `-Z self-profile`.
+//!
+//! Every `time` pass, codegen unit and LLVM module is recorded as an event
+//! with its start time, duration and the thread it ran on. At the end of the
+//! compilation the events are written out in the Chrome trace event format,
+//! which can be loaded into `about:tracing`.
+//!
+//! The profiler is installed per thread so that recording an event doesn't
+//! need access to the `Session`, which the trans worker threads don't have.
+
+use std::cell::{Cell, RefCell};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use serialize::json::as_json;
+
+thread_local!(static PROFILER: RefCell<Option<Arc<SelfProfiler>>> = RefCell::new(None));
+thread_local!(static THREAD_ID: Cell<usize> = Cell::new(0));
+
+struct Event {
+    category: &'static str,
+    name: String,
+    thread: usize,
+    start: Duration,
+    duration: Duration,
+}
+
+pub struct SelfProfiler {
+    start: Instant,
+    events: Mutex<Vec<Event>>,
+    next_thread: AtomicUsize,
+}
+
+impl SelfProfiler {
+    pub fn new() -> SelfProfiler {
+        SelfProfiler {
+            start: Instant::now(),
+            events: Mutex::new(Vec::new()),
+            next_thread: AtomicUsize::new(0),
+        }
+    }
+
+    /// Writes all events recorded so far to `path` as a JSON trace.
+    pub fn write_trace(&self, path: &Path) -> io::Result<()> {
+        let events = self.events.lock().unwrap();
+        let mut out = File::create(path)?;
+        write!(out, "{{\"traceEvents\":[")?;
+        for (i, event) in events.iter().enumerate() {
+            if i > 0 {
+                write!(out, ",")?;
+            }
+            write!(out,
+                   "\n{{\"name\":{},\"cat\":\"{}\",\"ph\":\"X\",\"ts\":{},\"dur\":{},\
+                    \"pid\":0,\"tid\":{}}}",
+                   as_json(&event.name),
+                   event.category,
+                   micros(event.start),
+                   micros(event.duration),
+                   event.thread)?;
+        }
+        write!(out, "\n]}}\n")?;
+        Ok(())
+    }
+}
+
+fn micros(dur: Duration) -> u64 {
+    dur.as_secs() * 1_000_000 + (dur.subsec_nanos() / 1_000) as u64
+}
+
+/// Makes `profiler` record the events of the current thread. Each thread it
+/// is installed on shows up as a separate track in the trace.
+pub fn install(profiler: Option<Arc<SelfProfiler>>) {
+    if let Some(ref profiler) = profiler {
+        let thread = profiler.next_thread.fetch_add(1, Ordering::SeqCst);
+        THREAD_ID.with(|id| id.set(thread));
+    }
+    PROFILER.with(|slot| *slot.borrow_mut() = profiler);
+}
+
+/// Runs `f`, recording it as an event in `category` if a profiler is
+/// installed on the current thread.
+pub fn profile<T, F>(category: &'static str, name: &str, f: F) -> T where
+    F: FnOnce() -> T,
+{
+    let profiler = match PROFILER.with(|slot| slot.borrow().clone()) {
+        Some(profiler) => profiler,
+        None => return f(),
+    };
+
+    let start = Instant::now();
+    let rv = f();
+    let duration = start.elapsed();
+
+    profiler.events.lock().unwrap().push(Event {
+        category: category,
+        name: name.to_string(),
+        thread: THREAD_ID.with(|id| id.get()),
+        start: start - profiler.start,
+        duration: duration,
+    });
+
+    rv
+}
@@ -44,7 +44,7 @@ use std::rc::Rc;
 use std::hash::{Hash, Hasher};
 use syntax::ast;
 use syntax_pos::{MultiSpan, Span};
-use errors::DiagnosticBuilder;
+use errors::{Applicability, DiagnosticBuilder};
 
 use rustc::hir;
 use rustc::hir::{FnDecl, Block};
@@ -941,10 +941,10 @@ impl<'a, 'tcx> BorrowckCtxt<'a, 'tcx> {
     {
         let cmt_path_or_string = self.cmt_to_path_or_string(&err.cmt);
 
-        let suggestion =
+        let (suggestion, applicability) =
             match self.tcx.sess.codemap().span_to_snippet(err.span) {
-                Ok(string) => format!("move {}", string),
-                Err(_) => format!("move |<args>| <body>")
+                Ok(string) => (format!("move {}", string), Applicability::MachineApplicable),
+                Err(_) => (format!("move |<args>| <body>"), Applicability::HasPlaceholders)
             };
 
         struct_span_err!(self.tcx.sess, err.span, E0373,
@@ -958,12 +958,14 @@ impl<'a, 'tcx> BorrowckCtxt<'a, 'tcx> {
             .span_label(err.span,
                        &format!("may outlive borrowed value {}",
                                 cmt_path_or_string))
-            .span_suggestion(err.span,
-                             &format!("to force the closure to take ownership of {} \
-                                       (and any other referenced variables), \
-                                       use the `move` keyword, as shown:",
-                                       cmt_path_or_string),
-                             suggestion)
+            .span_suggestion_with_applicability(err.span,
+                                                &format!("to force the closure to take \
+                                                          ownership of {} (and any other \
+                                                          referenced variables), use the \
+                                                          `move` keyword, as shown:",
+                                                         cmt_path_or_string),
+                                                suggestion,
+                                                applicability)
             .emit();
     }
 
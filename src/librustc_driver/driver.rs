@@ -88,6 +88,8 @@ pub fn compile_input(sess: &Session,
         }}
     }
 
+    let mut self_profile_trace = SelfProfileTrace { sess: sess, path: None };
+
     // We need nested scopes here, because the intermediate results can keep
     // large chunks of memory alive and we want to free them as soon as
     // possible to keep the peak memory usage low
@@ -96,6 +98,10 @@ pub fn compile_input(sess: &Session,
             Ok(krate) => krate,
             Err(mut parse_error) => {
                 parse_error.emit();
+                if sess.self_profiler.is_some() {
+                    let outputs = build_output_filenames(input, outdir, output, &[], sess);
+                    self_profile_trace.path = Some(outputs.with_extension("trace.json"));
+                }
                 return Err(1);
             }
         };
@@ -116,6 +122,7 @@ pub fn compile_input(sess: &Session,
         };
 
         let outputs = build_output_filenames(input, outdir, output, &krate.attrs, sess);
+        self_profile_trace.path = Some(outputs.with_extension("trace.json"));
         let crate_name = link::find_crate_name(Some(sess), &krate.attrs, input);
         let ExpansionResult { expanded_crate, defs, analysis, resolutions, mut hir_forest } = {
             phase_2_configure_and_expand(
@@ -199,6 +206,19 @@ pub fn compile_input(sess: &Session,
 
             result?;
 
+            // `--emit=metadata` on its own only needs the analysis results, so
+            // skip translation and LLVM altogether.
+            if sess.opts.output_types.contains_key(&OutputType::Metadata) &&
+               !sess.opts.output_types.should_trans() {
+                let mut mir_map = mir_map.unwrap();
+                prepare_mir_for_trans(tcx, &mut mir_map);
+                let metadata = time(sess.time_passes(), "write metadata", || {
+                    trans::encode_metadata_only(tcx, &mir_map, analysis, &incremental_hashes_map)
+                });
+                link::emit_metadata(sess, &metadata, &crate_name, &outputs);
+                return Err(0usize);
+            }
+
             if log_enabled!(::log::INFO) {
                 println!("Pre-trans");
                 tcx.print_debug_stats();
@@ -220,6 +240,10 @@ pub fn compile_input(sess: &Session,
         })??
     };
 
+    if sess.opts.output_types.contains_key(&OutputType::Metadata) {
+        link::emit_metadata(sess, &trans.metadata, &trans.link.crate_name, &outputs);
+    }
+
     let phase5_result = phase_5_run_llvm_passes(sess, &trans, &outputs);
 
     controller_entry_point!(after_llvm,
@@ -248,6 +272,28 @@ pub fn compile_input(sess: &Session,
     Ok(())
 }
 
+/// Writes the `-Z self-profile` trace once `compile_input` returns, however it
+/// returns: on success, on errors, when a controller stops the compilation
+/// early and when unwinding from a fatal error.
+struct SelfProfileTrace<'a> {
+    sess: &'a Session,
+    path: Option<PathBuf>,
+}
+
+impl<'a> Drop for SelfProfileTrace<'a> {
+    fn drop(&mut self) {
+        if let (Some(profiler), Some(path)) = (self.sess.self_profiler.as_ref(),
+                                               self.path.as_ref()) {
+            // The result of the compilation is already decided at this point,
+            // so a failure to write the trace can only be reported as a warning.
+            if let Err(e) = profiler.write_trace(path) {
+                self.sess.warn(&format!("failed to write self-profile trace to {}: {}",
+                                        path.display(), e));
+            }
+        }
+    }
+}
+
 fn keep_hygiene_data(sess: &Session) -> bool {
     sess.opts.debugging_opts.keep_hygiene_data
 }
@@ -1005,9 +1051,29 @@ pub fn phase_4_translate_to_llvm<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
          "resolving dependency formats",
          || dependency_format::calculate(&tcx.sess));
 
-    // Run the passes that transform the MIR into a more suitable for translation
-    // to LLVM code.
-    time(time_passes, "Prepare MIR codegen passes", || {
+    prepare_mir_for_trans(tcx, &mut mir_map);
+
+    let translation =
+        time(time_passes,
+             "translation",
+             move || trans::trans_crate(tcx, &mir_map, analysis, &incremental_hashes_map));
+
+    time(time_passes,
+         "assert dep graph",
+         || rustc_incremental::assert_dep_graph(tcx));
+
+    time(time_passes,
+         "serialize dep graph",
+         || rustc_incremental::save_dep_graph(tcx,
+                                              &incremental_hashes_map,
+                                              translation.link.crate_hash));
+    translation
+}
+
+/// Run the passes that transform the MIR into the form translation and the
+/// metadata encoder expect.
+fn prepare_mir_for_trans<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir_map: &mut MirMap<'tcx>) {
+    time(tcx.sess.time_passes(), "Prepare MIR codegen passes", || {
         let mut passes = ::rustc::mir::transform::Passes::new();
         passes.push_hook(box mir::transform::dump_mir::DumpMir);
         passes.push_pass(box mir::transform::no_landing_pads::NoLandingPads);
@@ -1029,24 +1095,8 @@ pub fn phase_4_translate_to_llvm<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
         passes.push_pass(box mir::transform::add_call_guards::AddCallGuards);
         passes.push_pass(box mir::transform::dump_mir::Marker("PreTrans"));
 
-        passes.run_passes(tcx, &mut mir_map);
+        passes.run_passes(tcx, mir_map);
     });
-
-    let translation =
-        time(time_passes,
-             "translation",
-             move || trans::trans_crate(tcx, &mir_map, analysis, &incremental_hashes_map));
-
-    time(time_passes,
-         "assert dep graph",
-         || rustc_incremental::assert_dep_graph(tcx));
-
-    time(time_passes,
-         "serialize dep graph",
-         || rustc_incremental::save_dep_graph(tcx,
-                                              &incremental_hashes_map,
-                                              translation.link.crate_hash));
-    translation
 }
 
 /// Run LLVM itself, producing a bitcode file, assembly file or object file
@@ -1110,6 +1160,9 @@ fn write_out_deps(sess: &Session, outputs: &OutputFilenames, crate_name: &str) {
                     out_filenames.push(p);
                 }
             }
+            OutputType::Metadata => {
+                out_filenames.push(link::filename_for_metadata(sess, crate_name, outputs));
+            }
             _ => {
                 out_filenames.push(file);
             }
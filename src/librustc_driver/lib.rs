@@ -86,6 +86,7 @@ use std::default::Default;
 use std::env;
 use std::io::{self, Read, Write};
 use std::iter::repeat;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::PathBuf;
 use std::process;
 use std::rc::Rc;
@@ -95,7 +96,7 @@ use std::thread;
 
 use rustc::session::early_error;
 
-use syntax::{ast, json};
+use syntax::{ast, json, sarif};
 use syntax::codemap::{CodeMap, FileLoader, RealFileLoader};
 use syntax::feature_gate::{GatedCfg, UnstableFeatures};
 use syntax::parse::{self, PResult};
@@ -134,6 +135,9 @@ pub fn abort_on_err<T>(result: Result<T, usize>, sess: &Session) -> T {
 pub fn run(args: Vec<String>) -> isize {
     monitor(move || {
         let (result, session) = run_compiler(&args, &mut RustcDefaultCalls);
+        if let Some(ref sess) = session {
+            sess.diagnostic().finish();
+        }
         if let Err(err_count) = result {
             if err_count > 0 {
                 match session {
@@ -223,9 +227,25 @@ pub fn run_compiler_with_file_loader<'a, L>(args: &[String],
 
     let plugins = sess.opts.debugging_opts.extra_plugins.clone();
     let control = callbacks.build_controller(&sess, &matches);
-    (driver::compile_input(&sess, &cstore, cfg, &input, &odir, &ofile,
-                           Some(plugins), &control),
-     Some(sess))
+    // A fatal error unwinds out of `compile_input`, but the suggestions
+    // collected until then should still be applied before it is reported.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        driver::compile_input(&sess, &cstore, cfg, &input, &odir, &ofile,
+                              Some(plugins), &control)
+    }));
+    if sess.opts.debugging_opts.apply_suggestions {
+        sess.apply_suggestions();
+    }
+    let mut result = match result {
+        Ok(result) => result,
+        Err(payload) => panic::resume_unwind(payload),
+    };
+    // Failing to write the fixes back is an error of its own, which the
+    // result of the compilation doesn't know about yet.
+    if result.is_ok() && sess.has_errors() {
+        result = Err(sess.err_count());
+    }
+    (result, Some(sess))
 }
 
 // Extract output directory and file from matches.
@@ -386,7 +406,11 @@ fn check_cfg(cfg: &ast::CrateConfig,
         config::ErrorOutputType::HumanReadable(color_config) => {
             Box::new(errors::emitter::EmitterWriter::stderr(color_config, None))
         }
+        config::ErrorOutputType::Short(color_config) => {
+            Box::new(errors::emitter::EmitterWriter::stderr_short(color_config, None))
+        }
         config::ErrorOutputType::Json => Box::new(json::JsonEmitter::basic()),
+        config::ErrorOutputType::Sarif => Box::new(sarif::SarifEmitter::basic()),
     };
     let handler = errors::Handler::with_emitter(true, false, emitter);
 
@@ -26,6 +26,10 @@ use term;
 pub trait Emitter {
     /// Emit a structured diagnostic.
     fn emit(&mut self, db: &DiagnosticBuilder);
+
+    /// Write out anything held back until all diagnostics are known, such as
+    /// a log describing all of them at once. Called at the end of the session.
+    fn finish(&mut self) {}
 }
 
 impl Emitter for EmitterWriter {
@@ -33,7 +37,11 @@ impl Emitter for EmitterWriter {
         let mut primary_span = db.span.clone();
         let mut children = db.children.clone();
         self.fix_multispans_in_std_macros(&mut primary_span, &mut children);
-        self.emit_messages_default(&db.level, &db.message, &db.code, &primary_span, &children);
+        if self.short_message {
+            self.emit_messages_short(&db.level, &db.message, &db.code, &primary_span, &children);
+        } else {
+            self.emit_messages_default(&db.level, &db.message, &db.code, &primary_span, &children);
+        }
     }
 }
 
@@ -60,6 +68,9 @@ impl ColorConfig {
 pub struct EmitterWriter {
     dst: Destination,
     cm: Option<Rc<CodeMapper>>,
+    /// Print one `file:line:col: level: message` line per (sub-)diagnostic
+    /// instead of annotated snippets.
+    short_message: bool,
 }
 
 struct FileWithAnnotatedLines {
@@ -86,13 +97,29 @@ impl EmitterWriter {
     pub fn stderr(color_config: ColorConfig,
                   code_map: Option<Rc<CodeMapper>>)
                   -> EmitterWriter {
+        EmitterWriter::stderr_with_format(color_config, code_map, false)
+    }
+
+    /// Like `stderr`, but prints the short one-line-per-message format.
+    pub fn stderr_short(color_config: ColorConfig,
+                        code_map: Option<Rc<CodeMapper>>)
+                        -> EmitterWriter {
+        EmitterWriter::stderr_with_format(color_config, code_map, true)
+    }
+
+    fn stderr_with_format(color_config: ColorConfig,
+                          code_map: Option<Rc<CodeMapper>>,
+                          short_message: bool)
+                          -> EmitterWriter {
         if color_config.use_color() {
             let dst = Destination::from_stderr();
             EmitterWriter { dst: dst,
-                            cm: code_map}
+                            cm: code_map,
+                            short_message: short_message }
         } else {
             EmitterWriter { dst: Raw(Box::new(io::stderr())),
-                            cm: code_map}
+                            cm: code_map,
+                            short_message: short_message }
         }
     }
 
@@ -100,7 +127,8 @@ impl EmitterWriter {
                code_map: Option<Rc<CodeMapper>>)
                -> EmitterWriter {
         EmitterWriter { dst: Raw(dst),
-                        cm: code_map}
+                        cm: code_map,
+                        short_message: false }
     }
 
     fn preprocess_annotations(&self, msp: &MultiSpan) -> Vec<FileWithAnnotatedLines> {
@@ -731,6 +759,87 @@ impl EmitterWriter {
             }
         }
     }
+
+    /// The `file:line:col` of the first primary span in `msp`, if any.
+    fn short_location(&self, msp: &MultiSpan) -> Option<String> {
+        for annotated_file in self.preprocess_annotations(msp) {
+            for line in &annotated_file.lines {
+                if let Some(ann) = line.annotations.iter().find(|ann| ann.is_primary) {
+                    return Some(format!("{}:{}:{}",
+                                        annotated_file.file.name,
+                                        line.line_index,
+                                        ann.start_col + 1));
+                }
+            }
+        }
+        None
+    }
+
+    fn append_short_message(&self,
+                            buffer: &mut StyledBuffer,
+                            line: usize,
+                            msp: &MultiSpan,
+                            msg: &str,
+                            code: &Option<String>,
+                            level: &Level,
+                            is_secondary: bool) {
+        match self.short_location(msp) {
+            Some(location) => {
+                buffer.append(line, &location, Style::LineAndColumn);
+                buffer.append(line, ": ", Style::NoStyle);
+            }
+            None if is_secondary => {
+                draw_note_separator(buffer, line, 2);
+            }
+            None => {}
+        }
+        buffer.append(line, &level.to_string(), Style::Level(level.clone()));
+        if let Some(ref code) = *code {
+            buffer.append(line, "[", Style::Level(level.clone()));
+            buffer.append(line, code, Style::Level(level.clone()));
+            buffer.append(line, "]", Style::Level(level.clone()));
+        }
+        buffer.append(line, ": ", Style::HeaderMsg);
+        buffer.append(line, msg, Style::HeaderMsg);
+    }
+
+    fn emit_messages_short(&mut self,
+                           level: &Level,
+                           message: &String,
+                           code: &Option<String>,
+                           span: &MultiSpan,
+                           children: &Vec<SubDiagnostic>) {
+        let mut buffer = StyledBuffer::new();
+        self.append_short_message(&mut buffer, 0, span, message, code, level, false);
+
+        for (i, child) in children.iter().enumerate() {
+            let line = i + 1;
+            match child.render_span {
+                Some(FullSpan(ref msp)) => {
+                    self.append_short_message(&mut buffer, line, msp, &child.message, &None,
+                                              &child.level, true);
+                }
+                Some(Suggestion(ref cs)) => {
+                    self.append_short_message(&mut buffer, line, &cs.msp, &child.message, &None,
+                                              &child.level, true);
+                    // Only inline substitutions that fit on the line.
+                    if cs.substitutes.len() == 1 && !cs.substitutes[0].contains('\n') {
+                        buffer.append(line, " `", Style::NoStyle);
+                        buffer.append(line, &cs.substitutes[0], Style::NoStyle);
+                        buffer.append(line, "`", Style::NoStyle);
+                    }
+                }
+                None => {
+                    self.append_short_message(&mut buffer, line, &child.span, &child.message,
+                                              &None, &child.level, true);
+                }
+            }
+        }
+
+        if let Err(e) = emit_to_destination(&buffer.render(), level, &mut self.dst) {
+            panic!("failed to emit error: {}", e);
+        }
+    }
 }
 
 fn draw_col_separator(buffer: &mut StyledBuffer, line: usize, col: usize) {
@@ -0,0 +1,101 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Applying machine-applicable suggestions to the source files they refer to.
+
+use {Applicability, CodeMapper, CodeSuggestion};
+use syntax_pos::{FileMap, FileName};
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::rc::Rc;
+
+/// A single replacement, as byte offsets into the file's source.
+struct Replacement {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+/// Rewrites the source files in place with every machine-applicable
+/// suggestion in `suggestions`, and returns the number of suggestions
+/// applied.
+///
+/// Suggestions pointing into imported or virtual files are skipped, as are
+/// suggestions overlapping one that has already been accepted. The latter
+/// usually just need another compiler run.
+pub fn apply_suggestions(cm: &CodeMapper, suggestions: &[CodeSuggestion]) -> io::Result<usize> {
+    let mut files: BTreeMap<FileName, (Rc<FileMap>, Vec<Replacement>)> = BTreeMap::new();
+    let mut applied = 0;
+
+    'suggestions: for suggestion in suggestions {
+        if suggestion.applicability != Applicability::MachineApplicable {
+            continue;
+        }
+
+        let mut parts = vec![];
+        let spans = suggestion.msp.primary_spans();
+        for (span, text) in spans.iter().zip(&suggestion.substitutes) {
+            let fm = cm.lookup_char_pos(span.lo).file;
+            if !fm.is_real_file() || fm.is_imported() || span.hi > fm.end_pos {
+                continue 'suggestions;
+            }
+            let replacement = Replacement {
+                start: (span.lo.0 - fm.start_pos.0) as usize,
+                end: (span.hi.0 - fm.start_pos.0) as usize,
+                text: text.clone(),
+            };
+            parts.push((fm, replacement));
+        }
+
+        // Only take the suggestion if all of its parts fit in.
+        let fits = parts.iter().all(|&(ref fm, ref part)| {
+            files.get(&fm.name).map_or(true, |&(_, ref accepted)| {
+                accepted.iter().all(|other| part.end <= other.start || other.end <= part.start)
+            })
+        });
+        if !fits {
+            continue;
+        }
+        for (fm, part) in parts {
+            files.entry(fm.name.clone())
+                 .or_insert_with(|| (fm.clone(), vec![]))
+                 .1.push(part);
+        }
+        applied += 1;
+    }
+
+    for (name, (fm, mut replacements)) in files {
+        let src = fm.src.as_ref().unwrap();
+        replacements.sort_by_key(|r| r.start);
+
+        let mut fixed = String::with_capacity(src.len());
+        let mut pos = 0;
+        for r in &replacements {
+            fixed.push_str(&src[pos..r.start]);
+            fixed.push_str(&r.text);
+            pos = r.end;
+        }
+        fixed.push_str(&src[pos..]);
+
+        // The codemap strips the byte order mark off the source, so check the
+        // file itself to keep it.
+        let mut start = vec![];
+        File::open(&name)?.take(3).read_to_end(&mut start)?;
+        let mut file = File::create(&name)?;
+        if start == b"\xef\xbb\xbf" {
+            file.write_all(b"\xef\xbb\xbf")?;
+        }
+        file.write_all(fixed.as_bytes())?;
+    }
+
+    Ok(applied)
+}
@@ -42,11 +42,12 @@ use self::RenderSpan::*;
 use emitter::{Emitter, EmitterWriter};
 
 use std::cell::{RefCell, Cell};
-use std::{error, fmt};
+use std::{error, fmt, mem};
 use std::rc::Rc;
 use std::thread::panicking;
 
 pub mod emitter;
+pub mod fixes;
 pub mod snippet;
 pub mod registry;
 pub mod styled_buffer;
@@ -73,6 +74,24 @@ pub enum RenderSpan {
 pub struct CodeSuggestion {
     pub msp: MultiSpan,
     pub substitutes: Vec<String>,
+    pub applicability: Applicability,
+}
+
+/// How confident the compiler is that a suggestion is what the user wants,
+/// which tools use to decide whether to apply it automatically.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, RustcEncodable)]
+pub enum Applicability {
+    /// The suggestion is definitely what the user intended and produces
+    /// valid code, so it can be applied without review.
+    MachineApplicable,
+    /// The suggestion may be what the user intended, but it is uncertain.
+    /// It should compile if applied.
+    MaybeIncorrect,
+    /// The suggestion contains placeholders like `(...)` and won't compile
+    /// as is.
+    HasPlaceholders,
+    /// The applicability of the suggestion is unknown.
+    Unspecified,
 }
 
 pub trait CodeMapper {
@@ -232,6 +251,7 @@ impl<'a> DiagnosticBuilder<'a> {
         }
 
         self.handler.emitter.borrow_mut().emit(&self);
+        self.handler.record_suggestions(&self);
         self.cancel();
         self.handler.panic_if_treat_err_as_bug();
 
@@ -333,9 +353,20 @@ impl<'a> DiagnosticBuilder<'a> {
                                                msg: &str,
                                                suggestion: String)
                                                -> &mut DiagnosticBuilder<'a> {
+        self.span_suggestion_with_applicability(sp, msg, suggestion, Applicability::Unspecified)
+    }
+    /// Like `span_suggestion`, but also records how safe the suggestion is
+    /// to apply automatically.
+    pub fn span_suggestion_with_applicability<S: Into<MultiSpan>>(&mut self,
+                                                                  sp: S,
+                                                                  msg: &str,
+                                                                  suggestion: String,
+                                                                  applicability: Applicability)
+                                                                  -> &mut DiagnosticBuilder<'a> {
         self.sub(Level::Help, msg, MultiSpan::new(), Some(Suggestion(CodeSuggestion {
             msp: sp.into(),
             substitutes: vec![suggestion],
+            applicability: applicability,
         })));
         self
     }
@@ -429,6 +460,8 @@ pub struct Handler {
     treat_err_as_bug: bool,
     continue_after_error: Cell<bool>,
     delayed_span_bug: RefCell<Option<(MultiSpan, String)>>,
+    collect_suggestions: Cell<bool>,
+    suggestions: RefCell<Vec<CodeSuggestion>>,
 }
 
 impl Handler {
@@ -451,6 +484,8 @@ impl Handler {
             treat_err_as_bug: treat_err_as_bug,
             continue_after_error: Cell::new(true),
             delayed_span_bug: RefCell::new(None),
+            collect_suggestions: Cell::new(false),
+            suggestions: RefCell::new(vec![]),
         }
     }
 
@@ -458,6 +493,37 @@ impl Handler {
         self.continue_after_error.set(continue_after_error);
     }
 
+    /// Keep the machine-applicable suggestions of emitted diagnostics so that
+    /// they can be applied later (see `take_suggestions`).
+    pub fn set_collect_suggestions(&self, collect_suggestions: bool) {
+        self.collect_suggestions.set(collect_suggestions);
+    }
+
+    /// Returns the suggestions collected so far, leaving none behind.
+    /// Lets the emitter write out what it holds back, once no more
+    /// diagnostics are coming.
+    pub fn finish(&self) {
+        self.emitter.borrow_mut().finish();
+    }
+
+    pub fn take_suggestions(&self) -> Vec<CodeSuggestion> {
+        mem::replace(&mut *self.suggestions.borrow_mut(), vec![])
+    }
+
+    fn record_suggestions(&self, db: &DiagnosticBuilder) {
+        if !self.collect_suggestions.get() {
+            return;
+        }
+        let mut suggestions = self.suggestions.borrow_mut();
+        for child in &db.children {
+            if let Some(Suggestion(ref suggestion)) = child.render_span {
+                if suggestion.applicability == Applicability::MachineApplicable {
+                    suggestions.push(suggestion.clone());
+                }
+            }
+        }
+    }
+
     pub fn struct_dummy<'a>(&'a self) -> DiagnosticBuilder<'a> {
         DiagnosticBuilder::new(self, Level::Cancelled, "")
     }
@@ -103,6 +103,9 @@ impl InlineAsmDiagnostic {
 pub enum Diagnostic {
     Optimization(OptimizationDiagnostic),
     InlineAsm(InlineAsmDiagnostic),
+    /// A problem with the profile used for profile-guided optimization, such
+    /// as a function whose profile no longer matches its code.
+    PGO(DiagnosticInfoRef),
 
     /// LLVM has other types that we do not wrap here.
     UnknownDiagnostic(DiagnosticInfoRef),
@@ -146,6 +149,8 @@ impl Diagnostic {
                 Optimization(OptimizationDiagnostic::unpack(OptimizationFailure, di))
             }
 
+            Dk::PGOProfile => PGO(di),
+
             _ => UnknownDiagnostic(di),
         }
     }
@@ -678,7 +678,8 @@ impl<'a> Context<'a> {
                         return false;
                     }
                 };
-                if file.starts_with("lib") && file.ends_with(".rlib") {
+                if file.starts_with("lib") &&
+                   (file.ends_with(".rlib") || file.ends_with(".rmeta")) {
                     return true
                 } else {
                     let (ref prefix, ref suffix) = dylibname;
@@ -689,16 +690,18 @@ impl<'a> Context<'a> {
                 }
                 sess.struct_err(&format!("extern location for {} is of an unknown type: {}",
                                          self.crate_name, loc.display()))
-                    .help(&format!("file name should be lib*.rlib or {}*.{}",
+                    .help(&format!("file name should be lib*.rlib, lib*.rmeta or {}*.{}",
                                    dylibname.0, dylibname.1))
                     .emit();
                 false
             });
 
             // Now that we have an iterator of good candidates, make sure
-            // there's at most one rlib and at most one dylib.
+            // there's at most one rlib and at most one dylib. Metadata-only
+            // rlibs from `--emit=metadata` are read just like rlibs.
             for loc in locs {
-                if loc.file_name().unwrap().to_str().unwrap().ends_with(".rlib") {
+                let file = loc.file_name().unwrap().to_str().unwrap().to_string();
+                if file.ends_with(".rlib") || file.ends_with(".rmeta") {
                     rlibs.insert(fs::canonicalize(&loc).unwrap(),
                                  PathKind::ExternFlag);
                 } else {
@@ -886,7 +889,11 @@ pub fn read_meta_section_name(_target: &Target) -> &'static str {
 pub fn list_file_metadata(target: &Target, path: &Path,
                           out: &mut io::Write) -> io::Result<()> {
     let filename = path.file_name().unwrap().to_str().unwrap();
-    let flavor = if filename.ends_with(".rlib") { CrateFlavor::Rlib } else { CrateFlavor::Dylib };
+    let flavor = if filename.ends_with(".rlib") || filename.ends_with(".rmeta") {
+        CrateFlavor::Rlib
+    } else {
+        CrateFlavor::Dylib
+    };
     match get_metadata_section(target, flavor, path) {
         Ok(metadata) => metadata.list_crate_metadata(out),
         Err(msg) => {
@@ -15,7 +15,7 @@ use super::rpath;
 use super::msvc;
 use session::config;
 use session::config::NoDebugInfo;
-use session::config::{OutputFilenames, Input, OutputType};
+use session::config::{OutputFilenames, Input, OutputType, Sanitizer};
 use session::filesearch;
 use session::search_paths::PathKind;
 use session::Session;
@@ -288,6 +288,47 @@ pub fn filename_for_input(sess: &Session,
     }
 }
 
+/// Returns where `--emit=metadata` writes its output: the path given on the
+/// command line, if any, or else `lib<crate-name>.rmeta` next to the rlib.
+pub fn filename_for_metadata(sess: &Session,
+                             crate_name: &str,
+                             outputs: &OutputFilenames) -> PathBuf {
+    let libname = format!("{}{}", crate_name, sess.opts.cg.extra_filename);
+    outputs.outputs.get(&OutputType::Metadata)
+                   .and_then(|s| s.to_owned())
+                   .or_else(|| outputs.single_output_file.clone())
+                   .unwrap_or_else(|| {
+                       outputs.out_directory.join(&format!("lib{}.rmeta", libname))
+                   })
+}
+
+/// Writes `metadata` out as a metadata-only rlib: an archive holding the
+/// crate metadata file and no object code. The crate loader reads it like
+/// an rlib, so dependent crates can be type-checked against it.
+pub fn emit_metadata(sess: &Session,
+                     metadata: &[u8],
+                     crate_name: &str,
+                     outputs: &OutputFilenames) {
+    let out_filename = filename_for_metadata(sess, crate_name, outputs);
+    if !is_writeable(&out_filename) {
+        sess.fatal(&format!("output file {} is not writeable -- check its \
+                            permissions", out_filename.display()));
+    }
+
+    let tmpdir = match TempDir::new("rustc") {
+        Ok(tmpdir) => tmpdir,
+        Err(err) => sess.fatal(&format!("couldn't create a temp dir: {}", err)),
+    };
+    let metadata_file = tmpdir.path().join(sess.cstore.metadata_filename());
+    if let Err(e) = fs::File::create(&metadata_file).and_then(|mut f| f.write_all(metadata)) {
+        sess.fatal(&format!("failed to write {}: {}", metadata_file.display(), e));
+    }
+
+    let mut ab = ArchiveBuilder::new(archive_config(sess, &out_filename, None));
+    ab.add_file(&metadata_file);
+    ab.build();
+}
+
 pub fn each_linked_rlib(sess: &Session,
                         f: &mut FnMut(CrateNum, &Path)) {
     let crates = sess.cstore.used_crates(LinkagePreference::RequireStatic).into_iter();
@@ -792,6 +833,7 @@ fn link_args(cmd: &mut Linker,
     //  2. Local native libraries
     //  3. Upstream rust libraries
     //  4. Upstream native libraries
+    //  5. The compiler-rt runtimes of instrumented executables
     //
     // The rationale behind this ordering is that those items lower down in the
     // list can't depend on items higher up in the list. For example nothing can
@@ -804,10 +846,15 @@ fn link_args(cmd: &mut Linker,
     // well, but they also can't depend on what we just started to add to the
     // link line. And finally upstream native libraries can't depend on anything
     // in this DAG so far because they're only dylibs and dylibs can only depend
-    // on other dylibs (e.g. other native deps).
+    // on other dylibs (e.g. other native deps). The runtimes come last as
+    // they provide the symbols the instrumented code calls into.
     add_local_native_libraries(cmd, sess);
     add_upstream_rust_crates(cmd, sess, crate_type, tmpdir);
     add_upstream_native_libraries(cmd, sess);
+    if crate_type == config::CrateTypeExecutable {
+        add_sanitizer_runtime(cmd, sess);
+        add_profiler_runtime(cmd, sess);
+    }
 
     // # Telling the linker what we're doing
 
@@ -849,6 +896,38 @@ fn link_args(cmd: &mut Linker,
     cmd.args(&used_link_args);
 }
 
+// # Compiler-rt runtimes
+//
+// Programs instrumented by `-Z sanitizer` or `-C profile-generate` need the
+// matching compiler-rt runtime. The build system copies these into the
+// target's lib directory in the sysroot as `librustc_{asan,lsan,msan,tsan}.a`
+// and `librustc_profiler.a`. The whole archive is linked in because the
+// runtime has to interpose on functions like `malloc`, or register itself
+// to run at exit, and nothing on the link line refers to it directly.
+fn add_sanitizer_runtime(cmd: &mut Linker, sess: &Session) {
+    let name = match sess.opts.debugging_opts.sanitizer {
+        Some(Sanitizer::Address) => "rustc_asan",
+        Some(Sanitizer::Leak) => "rustc_lsan",
+        Some(Sanitizer::Memory) => "rustc_msan",
+        Some(Sanitizer::Thread) => "rustc_tsan",
+        None => return,
+    };
+    link_runtime(cmd, sess, name);
+}
+
+fn add_profiler_runtime(cmd: &mut Linker, sess: &Session) {
+    if sess.opts.cg.profile_generate.is_some() {
+        link_runtime(cmd, sess, "rustc_profiler");
+    }
+}
+
+fn link_runtime(cmd: &mut Linker, sess: &Session, name: &str) {
+    let lib_path = sess.target_filesearch(PathKind::All).get_lib_path();
+    cmd.hint_static();
+    cmd.link_whole_staticlib(name, &[lib_path]);
+    cmd.hint_dynamic();
+}
+
 // # Native library linking
 //
 // User-supplied library search paths (-L on the command line). These are
@@ -13,12 +13,13 @@ use back::link::{get_linker, remove};
 use rustc_incremental::{save_trans_partition, in_incr_comp_dir};
 use session::config::{OutputFilenames, OutputTypes, Passes, SomePasses, AllPasses};
 use session::Session;
-use session::config::{self, OutputType};
+use session::config::{self, OutputType, Sanitizer};
 use llvm;
 use llvm::{ModuleRef, TargetMachineRef, PassManagerRef, DiagnosticInfoRef, ContextRef};
 use llvm::SMDiagnosticRef;
 use {CrateTranslation, ModuleLlvm, ModuleSource, ModuleTranslation};
 use util::common::time;
+use util::profiling;
 use util::common::path2cstr;
 use util::fs::link_or_copy;
 use errors::{self, Handler, Level, DiagnosticBuilder};
@@ -29,6 +30,7 @@ use context::{is_pie_binary, get_reloc_model};
 use std::ffi::{CStr, CString};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::ptr;
 use std::str;
 use std::sync::{Arc, Mutex};
 use std::sync::mpsc::channel;
@@ -241,6 +243,11 @@ pub struct ModuleConfig {
     /// Some(level) to optimize binary size, or None to not affect program size.
     opt_size: Option<llvm::CodeGenOptSize>,
 
+    /// Some(path) to instrument the code and write profiles to `path`.
+    pgo_gen: Option<String>,
+    /// Some(path) to optimize using the merged profile at `path`.
+    pgo_use: Option<String>,
+
     // Flags indicating which outputs to produce.
     emit_no_opt_bc: bool,
     emit_bc: bool,
@@ -274,6 +281,9 @@ impl ModuleConfig {
             opt_level: None,
             opt_size: None,
 
+            pgo_gen: None,
+            pgo_use: None,
+
             emit_no_opt_bc: false,
             emit_bc: false,
             emit_lto_bc: false,
@@ -412,6 +422,13 @@ unsafe extern "C" fn diagnostic_handler(info: DiagnosticInfoRef, user: *mut c_vo
             }
         }
 
+        llvm::diagnostic::PGO(info) => {
+            let msg = llvm::build_string(|s| {
+                llvm::LLVMRustWriteDiagnosticInfoToString(info, s)
+            }).expect("non-UTF8 PGO diagnostic");
+            cgcx.handler.warn(&msg);
+        }
+
         _ => (),
     }
 }
@@ -639,6 +656,16 @@ pub fn cleanup_llvm(trans: &CrateTranslation) {
     }
 }
 
+/// The LLVM instrumentation passes required by `-Z sanitizer`, if any.
+fn sanitizer_passes(sess: &Session) -> &'static [&'static str] {
+    match sess.opts.debugging_opts.sanitizer {
+        Some(Sanitizer::Address) => &["asan", "asan-module"],
+        Some(Sanitizer::Memory) => &["msan"],
+        Some(Sanitizer::Thread) => &["tsan"],
+        Some(Sanitizer::Leak) | None => &[],
+    }
+}
+
 pub fn run_passes(sess: &Session,
                   trans: &CrateTranslation,
                   output_types: &OutputTypes,
@@ -665,11 +692,19 @@ pub fn run_passes(sess: &Session,
     // Figure out what we actually need to build.
 
     let mut modules_config = ModuleConfig::new(tm, sess.opts.cg.passes.clone());
+    modules_config.passes.extend(sanitizer_passes(sess).iter().map(|s| s.to_string()));
     let mut metadata_config = ModuleConfig::new(tm, vec!());
 
     modules_config.opt_level = Some(get_llvm_opt_level(sess.opts.optimize));
     modules_config.opt_size = Some(get_llvm_opt_size(sess.opts.optimize));
 
+    modules_config.pgo_gen = sess.opts.cg.profile_generate.as_ref().map(|dir| {
+        // `%m` makes the runtime write one file per instrumented binary, so
+        // that an executable and the dylibs it loads don't clobber each other.
+        Path::new(dir).join("default_%m.profraw").to_string_lossy().into_owned()
+    });
+    modules_config.pgo_use = sess.opts.cg.profile_use.clone();
+
     // Save all versions of the bytecode if we're saving our temporaries.
     if sess.opts.cg.save_temps {
         modules_config.emit_no_opt_bc = true;
@@ -708,6 +743,7 @@ pub fn run_passes(sess: &Session,
                 modules_config.emit_obj = true;
                 metadata_config.emit_obj = true;
             },
+            OutputType::Metadata |
             OutputType::DepInfo => {}
         }
     }
@@ -844,6 +880,7 @@ pub fn run_passes(sess: &Session,
                 user_wants_objects = true;
                 copy_if_one_unit(OutputType::Object, true);
             }
+            OutputType::Metadata |
             OutputType::Exe |
             OutputType::DepInfo => {}
         }
@@ -956,11 +993,14 @@ fn execute_work_item(cgcx: &CodegenContext,
         match work_item.mtrans.source {
             ModuleSource::Translated(mllvm) => {
                 debug!("llvm-optimizing {:?}", work_item.mtrans.name);
-                optimize_and_codegen(cgcx,
-                                     work_item.mtrans,
-                                     mllvm,
-                                     work_item.config,
-                                     work_item.output_names);
+                let name = work_item.mtrans.name.clone();
+                profiling::profile("llvm-module", &name, || {
+                    optimize_and_codegen(cgcx,
+                                         work_item.mtrans,
+                                         mllvm,
+                                         work_item.config,
+                                         work_item.output_names);
+                });
             }
             ModuleSource::Preexisting(wp) => {
                 let incr_comp_session_dir = cgcx.incr_comp_session_dir
@@ -1023,8 +1063,11 @@ fn run_work_multithreaded(sess: &Session,
         futures.push(rx);
 
         let incr_comp_session_dir = sess.incr_comp_session_dir_opt().map(|r| r.clone());
+        let self_profiler = sess.self_profiler.clone();
 
         thread::Builder::new().name(format!("codegen-{}", i)).spawn(move || {
+            profiling::install(self_profiler);
+
             let diag_handler = Handler::with_emitter(true, false, box diag_emitter);
 
             // Must construct cgcx inside the proc because it has non-Send
@@ -1113,10 +1156,17 @@ pub unsafe fn with_llvm_pmb(llmod: ModuleRef,
     let opt_size = config.opt_size.unwrap_or(llvm::CodeGenOptSizeNone);
     let inline_threshold = config.inline_threshold;
 
+    let pgo_gen_path = config.pgo_gen.as_ref().map(|s| CString::new(s.as_bytes()).unwrap());
+    let pgo_use_path = config.pgo_use.as_ref().map(|s| CString::new(s.as_bytes()).unwrap());
+    let pgo_gen_ptr = pgo_gen_path.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+    let pgo_use_ptr = pgo_use_path.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+
     llvm::LLVMRustConfigurePassManagerBuilder(builder, opt_level,
                                               config.merge_functions,
                                               config.vectorize_slp,
-                                              config.vectorize_loop);
+                                              config.vectorize_loop,
+                                              pgo_gen_ptr,
+                                              pgo_use_ptr);
     llvm::LLVMPassManagerBuilderSetSizeLevel(builder, opt_size as u32);
 
     if opt_size != llvm::CodeGenOptSizeNone {
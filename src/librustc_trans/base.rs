@@ -44,6 +44,7 @@ use rustc::ty::adjustment::CustomCoerceUnsized;
 use rustc::dep_graph::{DepNode, WorkProduct};
 use rustc::hir::map as hir_map;
 use rustc::util::common::time;
+use rustc::util::profiling;
 use rustc::mir::mir_map::MirMap;
 use session::config::{self, NoDebugInfo};
 use rustc_incremental::IncrementalHashesMap;
@@ -77,6 +78,7 @@ use symbol_names_test;
 use trans_item::TransItem;
 use type_::Type;
 use type_of;
+use type_sizes;
 use value::Value;
 use Disr;
 use util::sha2::Sha256;
@@ -1331,7 +1333,8 @@ fn write_metadata(cx: &SharedCrateContext,
                         .borrow()
                         .iter()
                         .any(|ty| *ty != config::CrateTypeExecutable);
-    if !any_library {
+    let emit_metadata = cx.sess().opts.output_types.contains_key(&config::OutputType::Metadata);
+    if !any_library && !emit_metadata {
         return Vec::new();
     }
 
@@ -1341,6 +1344,12 @@ fn write_metadata(cx: &SharedCrateContext,
                                           cx.link_meta(),
                                           reachable_ids,
                                           cx.mir_map());
+    // Executables only need the metadata for `--emit=metadata`, not embedded
+    // in the binary.
+    if !any_library {
+        return metadata;
+    }
+
     let mut compressed = cstore.metadata_encoding_version().to_vec();
     compressed.extend_from_slice(&flate::deflate_bytes(&metadata));
 
@@ -1368,6 +1377,19 @@ fn write_metadata(cx: &SharedCrateContext,
     return metadata;
 }
 
+/// Encodes the crate metadata without translating anything, for
+/// `--emit=metadata` when no other output needs LLVM.
+pub fn encode_metadata_only<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                      mir_map: &MirMap<'tcx>,
+                                      analysis: ty::CrateAnalysis,
+                                      incremental_hashes_map: &IncrementalHashesMap)
+                                      -> Vec<u8> {
+    let ty::CrateAnalysis { export_map, reachable, name, .. } = analysis;
+    let reachable = filter_reachable_ids(tcx, reachable);
+    let link_meta = link::build_link_meta(incremental_hashes_map, name);
+    tcx.sess.cstore.encode_metadata(tcx, &export_map, &link_meta, &reachable, mir_map)
+}
+
 /// Find any symbols that are defined in one compilation unit, but not declared
 /// in any other compilation unit.  Give these symbols internal linkage.
 fn internalize_symbols<'a, 'tcx>(sess: &Session,
@@ -1681,10 +1703,12 @@ pub fn trans_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
         let cgu = ccx.codegen_unit();
         let trans_items = cgu.items_in_deterministic_order(tcx, &symbol_map);
 
-        tcx.dep_graph.with_task(cgu.work_product_dep_node(), || {
-            for (trans_item, linkage) in trans_items {
-                trans_item.predefine(&ccx, linkage);
-            }
+        profiling::profile("codegen-unit", &format!("predefine {}", cgu.name()), || {
+            tcx.dep_graph.with_task(cgu.work_product_dep_node(), || {
+                for (trans_item, linkage) in trans_items {
+                    trans_item.predefine(&ccx, linkage);
+                }
+            });
         });
     }
 
@@ -1692,33 +1716,40 @@ pub fn trans_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     for ccx in crate_context_list.iter_need_trans() {
         let cgu = ccx.codegen_unit();
         let trans_items = cgu.items_in_deterministic_order(tcx, &symbol_map);
-        tcx.dep_graph.with_task(cgu.work_product_dep_node(), || {
-            for (trans_item, _) in trans_items {
-                trans_item.define(&ccx);
-            }
-
-            // If this codegen unit contains the main function, also create the
-            // wrapper here
-            maybe_create_entry_wrapper(&ccx);
+        profiling::profile("codegen-unit", &format!("define {}", cgu.name()), || {
+            tcx.dep_graph.with_task(cgu.work_product_dep_node(), || {
+                for (trans_item, _) in trans_items {
+                    trans_item.define(&ccx);
+                }
 
-            // Run replace-all-uses-with for statics that need it
-            for &(old_g, new_g) in ccx.statics_to_rauw().borrow().iter() {
-                unsafe {
-                    let bitcast = llvm::LLVMConstPointerCast(new_g, llvm::LLVMTypeOf(old_g));
-                    llvm::LLVMReplaceAllUsesWith(old_g, bitcast);
-                    llvm::LLVMDeleteGlobal(old_g);
+                // If this codegen unit contains the main function, also create the
+                // wrapper here
+                maybe_create_entry_wrapper(&ccx);
+
+                // Run replace-all-uses-with for statics that need it
+                for &(old_g, new_g) in ccx.statics_to_rauw().borrow().iter() {
+                    unsafe {
+                        let bitcast = llvm::LLVMConstPointerCast(new_g,
+                                                                 llvm::LLVMTypeOf(old_g));
+                        llvm::LLVMReplaceAllUsesWith(old_g, bitcast);
+                        llvm::LLVMDeleteGlobal(old_g);
+                    }
                 }
-            }
 
-            // Finalize debuginfo
-            if ccx.sess().opts.debuginfo != NoDebugInfo {
-                debuginfo::finalize(&ccx);
-            }
+                // Finalize debuginfo
+                if ccx.sess().opts.debuginfo != NoDebugInfo {
+                    debuginfo::finalize(&ccx);
+                }
+            });
         });
     }
 
     symbol_names_test::report_symbol_names(&shared_ccx);
 
+    if tcx.sess.opts.debugging_opts.print_type_sizes {
+        type_sizes::print_type_sizes(tcx);
+    }
+
     if shared_ccx.sess().trans_stats() {
         let stats = shared_ccx.stats();
         println!("--- trans stats ---");
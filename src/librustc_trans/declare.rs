@@ -21,6 +21,7 @@
 //! * When in doubt, define.
 use llvm::{self, ValueRef};
 use llvm::AttributePlace::Function;
+use rustc::session::config::Sanitizer;
 use rustc::ty;
 use abi::{Abi, FnType};
 use attributes;
@@ -69,6 +70,20 @@ fn declare_raw_fn(ccx: &CrateContext, name: &str, callconv: llvm::CallConv, ty:
         llvm::Attribute::NoRedZone.apply_llfn(Function, llfn);
     }
 
+    match ccx.tcx().sess.opts.debugging_opts.sanitizer {
+        Some(Sanitizer::Address) => {
+            llvm::Attribute::SanitizeAddress.apply_llfn(Function, llfn);
+        },
+        Some(Sanitizer::Memory) => {
+            llvm::Attribute::SanitizeMemory.apply_llfn(Function, llfn);
+        },
+        Some(Sanitizer::Thread) => {
+            llvm::Attribute::SanitizeThread.apply_llfn(Function, llfn);
+        },
+        // LeakSanitizer only needs its runtime, there is no instrumentation.
+        Some(Sanitizer::Leak) | None => {},
+    }
+
     match ccx.tcx().sess.opts.cg.opt_level.as_ref().map(String::as_ref) {
         Some("s") => {
             llvm::Attribute::OptimizeForSize.apply_llfn(Function, llfn);
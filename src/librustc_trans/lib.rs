@@ -65,7 +65,7 @@ pub use rustc::middle;
 pub use rustc::lint;
 pub use rustc::util;
 
-pub use base::trans_crate;
+pub use base::{trans_crate, encode_metadata_only};
 pub use disr::Disr;
 
 pub mod back {
@@ -129,6 +129,7 @@ mod trans_item;
 mod tvec;
 mod type_;
 mod type_of;
+mod type_sizes;
 mod value;
 
 #[derive(Clone)]
@@ -0,0 +1,221 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The `-Z print-type-sizes` report.
+//!
+//! Once translation is done the layout cache holds the layout of every
+//! type that was translated. This prints the size and alignment of each
+//! struct, enum, union and closure among them, largest first, along with
+//! the offset and size of their fields and any padding in between.
+
+use rustc::infer::InferCtxt;
+use rustc::traits::Reveal;
+use rustc::ty::{self, Ty, TyCtxt, TypeFoldable};
+use rustc::ty::layout::{Layout, Struct, Size};
+use rustc::util::nodemap::FnvHashSet;
+
+struct FieldInfo {
+    name: String,
+    offset: u64,
+    size: u64,
+}
+
+struct VariantInfo {
+    /// The name of the enum variant, `None` for structs, unions and closures.
+    name: Option<String>,
+    fields: Vec<FieldInfo>,
+}
+
+struct TypeSizeInfo {
+    name: String,
+    size: u64,
+    align: u64,
+    discr_size: Option<u64>,
+    variants: Vec<VariantInfo>,
+}
+
+pub fn print_type_sizes<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) {
+    // Collect the layouts first, computing the field layouts below may add
+    // to the cache.
+    let layouts = tcx.layout_cache.borrow().iter().map(|(&ty, &layout)| {
+        (ty, layout)
+    }).collect::<Vec<_>>();
+
+    let mut infos = tcx.infer_ctxt(None, None, Reveal::All).enter(|infcx| {
+        let mut seen = FnvHashSet();
+        layouts.into_iter().filter_map(|(ty, layout)| {
+            type_size_info(&infcx, ty, layout)
+        }).filter(|info| seen.insert(info.name.clone())).collect::<Vec<_>>()
+    });
+
+    // Largest first, ties broken by name to keep the output stable.
+    infos.sort_by(|a, b| (b.size, &a.name).cmp(&(a.size, &b.name)));
+
+    for info in &infos {
+        print_type_size_info(info);
+    }
+}
+
+fn type_size_info<'a, 'tcx>(infcx: &InferCtxt<'a, 'tcx, 'tcx>,
+                            ty: Ty<'tcx>,
+                            layout: &Layout)
+                            -> Option<TypeSizeInfo> {
+    // Layouts computed before translation, e.g. while checking transmutes,
+    // may still refer to type parameters.
+    if ty.needs_subst() || ty.has_projection_types() {
+        return None;
+    }
+
+    let tcx = infcx.tcx;
+    let dl = &tcx.data_layout;
+
+    // The names and types of the fields of each variant.
+    let variants = match ty.sty {
+        ty::TyAdt(def, substs) => {
+            def.variants.iter().map(|v| {
+                let name = if def.is_enum() { Some(v.name.to_string()) } else { None };
+                let fields = v.fields.iter().map(|f| {
+                    (f.name.to_string(), f.ty(tcx, substs))
+                }).collect::<Vec<_>>();
+                (name, fields)
+            }).collect::<Vec<_>>()
+        }
+        ty::TyClosure(_, ref substs) => {
+            let fields = substs.upvar_tys.iter().enumerate().map(|(i, &ty)| {
+                (i.to_string(), ty)
+            }).collect::<Vec<_>>();
+            vec![(None, fields)]
+        }
+        _ => return None,
+    };
+
+    let field_size = |ty: Ty<'tcx>| {
+        match ty.layout(infcx) {
+            Ok(layout) => layout.size(dl).bytes(),
+            Err(e) => bug!("failed to get layout for `{}`: {}", ty, e),
+        }
+    };
+
+    // `skip` is the number of leading fields in `st` which aren't fields of
+    // the type, i.e. the discriminant of a `General` enum.
+    let struct_variant = |name: &Option<String>, fields: &[(String, Ty<'tcx>)],
+                          st: &Struct, skip: usize| {
+        VariantInfo {
+            name: name.clone(),
+            fields: fields.iter().enumerate().map(|(i, &(ref name, ty))| {
+                FieldInfo {
+                    name: name.clone(),
+                    offset: st.offset_of_field(i + skip).bytes(),
+                    size: field_size(ty),
+                }
+            }).collect(),
+        }
+    };
+
+    let empty_variant = |name: &Option<String>| {
+        VariantInfo { name: name.clone(), fields: vec![] }
+    };
+
+    let (discr_size, variants) = match *layout {
+        Layout::Univariant { ref variant, .. } => {
+            let (ref name, ref fields) = variants[0];
+            (None, vec![struct_variant(name, fields, variant, 0)])
+        }
+        Layout::UntaggedUnion { .. } => {
+            let (ref name, ref fields) = variants[0];
+            let fields = fields.iter().map(|&(ref name, ty)| {
+                FieldInfo { name: name.clone(), offset: 0, size: field_size(ty) }
+            }).collect();
+            (None, vec![VariantInfo { name: name.clone(), fields: fields }])
+        }
+        Layout::CEnum { discr, .. } => {
+            (Some(discr.size()), variants.iter().map(|v| empty_variant(&v.0)).collect())
+        }
+        Layout::General { discr, variants: ref structs, .. } => {
+            let variants = variants.iter().zip(structs).map(|(v, st)| {
+                struct_variant(&v.0, &v.1, st, 1)
+            }).collect();
+            (Some(discr.size()), variants)
+        }
+        Layout::RawNullablePointer { nndiscr, value } => {
+            let variants = variants.iter().enumerate().map(|(i, v)| {
+                if i as u64 == nndiscr {
+                    VariantInfo {
+                        name: v.0.clone(),
+                        fields: vec![FieldInfo {
+                            name: v.1[0].0.clone(),
+                            offset: 0,
+                            size: value.size(dl).bytes(),
+                        }],
+                    }
+                } else {
+                    empty_variant(&v.0)
+                }
+            }).collect();
+            (None, variants)
+        }
+        Layout::StructWrappedNullablePointer { nndiscr, ref nonnull, .. } => {
+            let variants = variants.iter().enumerate().map(|(i, v)| {
+                if i as u64 == nndiscr {
+                    struct_variant(&v.0, &v.1, nonnull, 0)
+                } else {
+                    empty_variant(&v.0)
+                }
+            }).collect();
+            (None, variants)
+        }
+        // SIMD vectors and the like, which have no interesting structure.
+        _ => (None, vec![]),
+    };
+
+    Some(TypeSizeInfo {
+        name: ty.to_string(),
+        size: layout.size(dl).bytes(),
+        align: layout.align(dl).abi(),
+        discr_size: discr_size.map(|size: Size| size.bytes()),
+        variants: variants,
+    })
+}
+
+fn print_type_size_info(info: &TypeSizeInfo) {
+    println!("print-type-size type: `{}`: {} bytes, alignment: {} bytes",
+             info.name, info.size, info.align);
+
+    let discr_size = info.discr_size.unwrap_or(0);
+    if let Some(discr_size) = info.discr_size {
+        println!("print-type-size     discriminant: {} bytes", discr_size);
+    }
+
+    for variant in &info.variants {
+        // Fields of enum variants are nested under the variant.
+        let indent = match variant.name {
+            Some(ref name) => {
+                let size = variant.fields.last().map_or(discr_size, |f| f.offset + f.size);
+                println!("print-type-size     variant `{}`: {} bytes", name, size);
+                "        "
+            }
+            None => "    ",
+        };
+
+        let mut end = discr_size;
+        for field in &variant.fields {
+            if field.offset > end {
+                println!("print-type-size {}padding: {} bytes", indent, field.offset - end);
+            }
+            println!("print-type-size {}field `.{}`: {} bytes, offset: {} bytes",
+                     indent, field.name, field.size, field.offset);
+            end = end.max(field.offset + field.size);
+        }
+
+        if variant.name.is_none() && info.size > end {
+            println!("print-type-size {}end padding: {} bytes", indent, info.size - end);
+        }
+    }
+}
@@ -48,6 +48,7 @@ use rustc::ty::{self, Ty, TypeFoldable};
 use rustc::ty::cast::{CastKind, CastTy};
 use syntax::ast;
 use syntax_pos::Span;
+use errors::Applicability;
 use util::common::ErrorReported;
 
 /// Reifies a cast check to be checked once we have full type information for
@@ -224,9 +225,11 @@ impl<'a, 'gcx, 'tcx> CastCheck<'tcx> {
                 if self.cast_ty.is_trait() {
                     match fcx.tcx.sess.codemap().span_to_snippet(self.cast_span) {
                         Ok(s) => {
-                            err.span_suggestion(self.cast_span,
-                                                "try casting to a reference instead:",
-                                                format!("&{}{}", mtstr, s));
+                            err.span_suggestion_with_applicability(
+                                self.cast_span,
+                                "try casting to a reference instead:",
+                                format!("&{}{}", mtstr, s),
+                                Applicability::MaybeIncorrect);
                         },
                         Err(_) =>
                             span_help!(err, self.cast_span,
@@ -241,9 +244,11 @@ impl<'a, 'gcx, 'tcx> CastCheck<'tcx> {
             ty::TyBox(..) => {
                 match fcx.tcx.sess.codemap().span_to_snippet(self.cast_span) {
                     Ok(s) => {
-                        err.span_suggestion(self.cast_span,
-                                            "try casting to a `Box` instead:",
-                                            format!("Box<{}>", s));
+                        err.span_suggestion_with_applicability(
+                            self.cast_span,
+                            "try casting to a `Box` instead:",
+                            format!("Box<{}>", s),
+                            Applicability::MaybeIncorrect);
                     },
                     Err(_) =>
                         span_help!(err, self.cast_span, "did you mean `Box<{}>`?", tstr),
@@ -471,6 +471,8 @@ pub fn build_impl<'a, 'tcx>(cx: &DocContext,
             generics: (ty.generics, &predicates).clean(cx),
             items: trait_items,
             polarity: Some(polarity.clean(cx)),
+            synthetic: false,
+            blanket_impl: None,
         }),
         source: clean::Span::empty(),
         name: None,
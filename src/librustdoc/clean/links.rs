@@ -0,0 +1,365 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Resolution of intra-doc links, i.e. markdown references such as `[Foo]` or
+//! `[crate::module::Foo::method]` that name an item by its path.
+
+use rustc::hir;
+use rustc::hir::def::Def;
+use rustc::hir::def_id::{DefId, CRATE_DEF_INDEX};
+use rustc::hir::map::DefPathData;
+use rustc::ty;
+use rustc::util::nodemap::FnvHashMap;
+use syntax::ast;
+
+use core::DocContext;
+use fold::DocFolder;
+use passes;
+
+use super::{Crate, Item, register_def};
+
+/// Resolves the intra-doc links found in the documentation of the local items
+/// of `krate`. Resolved links are recorded in the `RenderInfo` so that they can
+/// be rewritten into URLs when rendering; unresolved ones are recorded in the
+/// crate for the `lint-docs` pass to report.
+pub fn collect_intra_doc_links(cx: &DocContext, krate: Crate) -> Crate {
+    let mut collector = LinkCollector { cx: cx, unresolved: FnvHashMap() };
+    let mut krate = collector.fold_crate(krate);
+    krate.unresolved_links = collector.unresolved;
+    krate
+}
+
+struct LinkCollector<'a, 'b: 'a, 'tcx: 'b> {
+    cx: &'a DocContext<'b, 'tcx>,
+    unresolved: FnvHashMap<DefId, Vec<String>>,
+}
+
+impl<'a, 'b, 'tcx> DocFolder for LinkCollector<'a, 'b, 'tcx> {
+    fn fold_item(&mut self, item: Item) -> Option<Item> {
+        if item.def_id.is_local() {
+            self.resolve_item_links(&item);
+        }
+        self.fold_item_recur(item)
+    }
+}
+
+impl<'a, 'b, 'tcx> LinkCollector<'a, 'b, 'tcx> {
+    fn resolve_item_links(&mut self, item: &Item) {
+        let tcx = self.cx.tcx();
+        let node_id = match tcx.map.as_local_node_id(item.def_id) {
+            Some(node_id) => node_id,
+            None => return,
+        };
+        // Paths are resolved relative to the module the item is in, or to the
+        // module itself when documenting a module.
+        let module = if item.is_mod() {
+            item.def_id
+        } else {
+            tcx.map.local_def_id(tcx.map.get_module_parent(node_id))
+        };
+
+        // Code blocks and reference definitions may span several doc
+        // attributes, so look at the documentation as rendered, once
+        // `collapse-docs` has joined them.
+        let mut links = Vec::new();
+        let mut unresolved = Vec::new();
+        for (label, path) in link_candidates(&passes::collapsed_docs(&item.attrs)) {
+            match self.resolve(module, &path) {
+                Some((did, fragment)) => links.push((label, did, fragment)),
+                None => unresolved.push(path),
+            }
+        }
+        if !links.is_empty() {
+            self.cx.renderinfo.borrow_mut().intra_doc_links.insert(item.def_id, links);
+        }
+        if !unresolved.is_empty() {
+            self.unresolved.insert(item.def_id, unresolved);
+        }
+    }
+
+    /// Resolves `path` from within `module`, returning the item whose page
+    /// should be linked to, along with the anchor on that page if the path
+    /// names a field, variant or associated item.
+    fn resolve(&self, module: DefId, path: &str) -> Option<(DefId, Option<String>)> {
+        let tcx = self.cx.tcx();
+        let root = DefId::local(CRATE_DEF_INDEX);
+        let segments = path.split("::").collect::<Vec<_>>();
+        let (mut def, rest) = match segments[0] {
+            "" | "crate" => (Def::Mod(root), &segments[1..]),
+            "self" => (Def::Mod(module), &segments[1..]),
+            "super" => (Def::Mod(module), &segments[..]),
+            name => {
+                // Like in `use` paths, names that aren't found in the current
+                // module are looked up in the crate root (which is also where
+                // the prelude gets imported).
+                let def = self.lookup_in_module(module, name, &mut vec![])
+                              .or_else(|| self.lookup_in_module(root, name, &mut vec![]));
+                match def {
+                    Some(def) => (def, &segments[1..]),
+                    None => return None,
+                }
+            }
+        };
+
+        for (i, &segment) in rest.iter().enumerate() {
+            def = match def {
+                Def::Mod(did) if segment == "super" => match tcx.parent_def_id(did) {
+                    Some(parent) => Def::Mod(parent),
+                    None => return None,
+                },
+                Def::Mod(did) => match self.lookup_in_module(did, segment, &mut vec![]) {
+                    Some(def) => def,
+                    None => return None,
+                },
+                Def::Struct(..) | Def::Union(..) | Def::Enum(..) | Def::Trait(..)
+                        if i == rest.len() - 1 => {
+                    return self.lookup_associated(def, segment);
+                }
+                _ => return None,
+            };
+        }
+
+        match def {
+            Def::Variant(did) => {
+                let fragment = format!("variant.{}", tcx.item_name(did));
+                Some((register_def(self.cx, def), Some(fragment)))
+            }
+            Def::Mod(..) | Def::Struct(..) | Def::Union(..) | Def::Enum(..) |
+            Def::Trait(..) | Def::Fn(..) | Def::TyAlias(..) | Def::Const(..) |
+            Def::Static(..) => Some((register_def(self.cx, def), None)),
+            _ => None,
+        }
+    }
+
+    /// Looks up `name` among the items, imports and extern crates of `module`.
+    fn lookup_in_module(&self, module: DefId, name: &str,
+                        visited: &mut Vec<DefId>) -> Option<Def> {
+        let tcx = self.cx.tcx();
+        if visited.contains(&module) {
+            return None
+        }
+        visited.push(module);
+
+        let node_id = match tcx.map.as_local_node_id(module) {
+            Some(node_id) => node_id,
+            None => {
+                let cstore = &tcx.sess.cstore;
+                return cstore.item_children(module).into_iter()
+                             .find(|child| &*child.name.as_str() == name)
+                             .and_then(|child| cstore.describe_def(child.def_id))
+                             .map(|def| self.struct_for_ctor(def));
+            }
+        };
+        let m = if node_id == ast::CRATE_NODE_ID {
+            &tcx.map.krate().module
+        } else {
+            match tcx.map.expect_item(node_id).node {
+                hir::ItemMod(ref m) => m,
+                _ => return None,
+            }
+        };
+
+        let mut globs = Vec::new();
+        for item_id in m.item_ids.iter() {
+            let item = tcx.map.expect_item(item_id.id);
+            let did = tcx.map.local_def_id(item.id);
+            let def = match item.node {
+                hir::ItemUse(ref view_path) => match view_path.node {
+                    hir::ViewPathSimple(rename, _) if &*rename.as_str() == name => {
+                        tcx.expect_def_or_none(item.id)
+                    }
+                    hir::ViewPathList(_, ref list) => {
+                        list.iter().find(|li| {
+                            &*li.node.rename.unwrap_or(li.node.name).as_str() == name
+                        }).and_then(|li| tcx.expect_def_or_none(li.node.id))
+                    }
+                    hir::ViewPathGlob(_) => {
+                        globs.push(item.id);
+                        None
+                    }
+                    _ => None,
+                },
+                _ if &*item.name.as_str() != name => None,
+                hir::ItemExternCrate(_) => {
+                    tcx.sess.cstore.extern_mod_stmt_cnum(item.id).map(|cnum| {
+                        Def::Mod(DefId { krate: cnum, index: CRATE_DEF_INDEX })
+                    })
+                }
+                hir::ItemMod(..) => Some(Def::Mod(did)),
+                hir::ItemStruct(..) => Some(Def::Struct(did)),
+                hir::ItemUnion(..) => Some(Def::Union(did)),
+                hir::ItemEnum(..) => Some(Def::Enum(did)),
+                hir::ItemTrait(..) => Some(Def::Trait(did)),
+                hir::ItemFn(..) => Some(Def::Fn(did)),
+                hir::ItemTy(..) => Some(Def::TyAlias(did)),
+                hir::ItemConst(..) => Some(Def::Const(did)),
+                hir::ItemStatic(_, mutbl, _) => Some(Def::Static(did, mutbl == hir::MutMutable)),
+                _ => None,
+            };
+            if let Some(def) = def {
+                return Some(self.struct_for_ctor(def))
+            }
+        }
+
+        // Glob imports are only consulted once the explicitly named items
+        // and imports didn't match, as they would be shadowed by those.
+        for id in globs {
+            if let Some(Def::Mod(did)) = tcx.expect_def_or_none(id) {
+                if let Some(def) = self.lookup_in_module(did, name, visited) {
+                    return Some(def)
+                }
+            }
+        }
+        None
+    }
+
+    /// Looks up the field, variant or associated item `name` of the type or
+    /// trait `def`.
+    fn lookup_associated(&self, def: Def, name: &str) -> Option<(DefId, Option<String>)> {
+        let tcx = self.cx.tcx();
+        let kind = match def {
+            Def::Trait(did) => {
+                tcx.trait_items(did).iter().find(|item| &*item.name().as_str() == name)
+                                    .map(|item| assoc_item_kind(item, true))
+            }
+            Def::Enum(did) => {
+                let adt = tcx.lookup_adt_def(did);
+                adt.variants.iter().find(|variant| &*variant.name.as_str() == name)
+                                   .map(|_| "variant")
+                                   .or_else(|| self.lookup_inherent_item(did, name))
+            }
+            Def::Struct(did) | Def::Union(did) => {
+                let adt = tcx.lookup_adt_def(did);
+                adt.struct_variant().fields.iter()
+                                           .find(|field| &*field.name.as_str() == name)
+                                           .map(|_| "structfield")
+                                           .or_else(|| self.lookup_inherent_item(did, name))
+            }
+            _ => None,
+        };
+        kind.map(|kind| (register_def(self.cx, def), Some(format!("{}.{}", kind, name))))
+    }
+
+    /// Looks up `name` among the items of the inherent impls of `did`.
+    fn lookup_inherent_item(&self, did: DefId, name: &str) -> Option<&'static str> {
+        let tcx = self.cx.tcx();
+        tcx.populate_inherent_implementations_for_type_if_necessary(did);
+        let impls = match tcx.inherent_impls.borrow().get(&did) {
+            Some(impls) => impls.clone(),
+            None => return None,
+        };
+        for &impl_did in impls.iter() {
+            for &item_did in tcx.impl_or_trait_items(impl_did).iter() {
+                let item = tcx.impl_or_trait_item(item_did);
+                if &*item.name().as_str() == name {
+                    return Some(assoc_item_kind(&item, false))
+                }
+            }
+        }
+        None
+    }
+
+    /// Imports of tuple and unit structs may resolve to the struct's
+    /// constructor rather than to the struct itself.
+    fn struct_for_ctor(&self, def: Def) -> Def {
+        let tcx = self.cx.tcx();
+        match def {
+            Def::Struct(did) => match tcx.def_key(did).disambiguated_data.data {
+                DefPathData::StructCtor => Def::Struct(tcx.parent_def_id(did).unwrap_or(did)),
+                _ => def,
+            },
+            _ => def,
+        }
+    }
+}
+
+/// Returns the prefix of the anchor rustdoc uses for an associated item.
+fn assoc_item_kind(item: &ty::ImplOrTraitItem, in_trait: bool) -> &'static str {
+    match *item {
+        ty::MethodTraitItem(ref method) if in_trait && !method.has_body => "tymethod",
+        ty::MethodTraitItem(_) => "method",
+        ty::ConstTraitItem(_) => "associatedconstant",
+        ty::TypeTraitItem(_) => "associatedtype",
+    }
+}
+
+/// Finds the shortcut (`[Foo]`), collapsed (`[Foo][]`) and full (`[text][Foo]`)
+/// reference links of `doc` whose label looks like a path and which don't
+/// have a reference definition already. Returns the labels along with the
+/// paths they name.
+fn link_candidates(doc: &str) -> Vec<(String, String)> {
+    let mut defined = Vec::new();
+    let mut labels = Vec::new();
+    let mut in_code_block = false;
+
+    for line in doc.lines() {
+        let trimmed = line.trim_left();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue
+        }
+        if in_code_block {
+            continue
+        }
+
+        let code_spans = code_spans(line);
+        let mut pos = 0;
+        while let Some(offset) = line[pos..].find('[') {
+            let start = pos + offset;
+            let end = match line[start..].find(']') {
+                Some(len) => start + len,
+                None => break,
+            };
+            pos = end + 1;
+
+            let label = &line[start + 1..end];
+            let before = line[..start].chars().next_back();
+            let after = &line[end + 1..];
+            if code_spans.iter().any(|&(lo, hi)| lo < start && start < hi) ||
+               before.map_or(false, |c| c.is_alphanumeric() || "_!\\".contains(c)) {
+                continue
+            }
+            if after.starts_with(':') && line[..start].trim().is_empty() {
+                defined.push(label.to_lowercase());
+            } else if after.starts_with("[]") {
+                labels.push(label);
+                pos += 2;
+            } else if !after.starts_with('(') && !after.starts_with('[') {
+                labels.push(label);
+            }
+        }
+    }
+
+    let mut candidates: Vec<(String, String)> = Vec::new();
+    for label in labels {
+        let path = label.trim_matches('`');
+        let path = path.trim_right_matches("()").trim_right_matches('!');
+        if !is_path(path) || defined.contains(&label.to_lowercase()) ||
+           candidates.iter().any(|&(ref l, _)| *l == label) {
+            continue
+        }
+        candidates.push((label.to_string(), path.to_string()));
+    }
+    candidates
+}
+
+/// Returns the byte ranges of the inline code spans of `line`.
+fn code_spans(line: &str) -> Vec<(usize, usize)> {
+    let ticks = line.match_indices('`').map(|(i, _)| i).collect::<Vec<_>>();
+    ticks.chunks(2).filter(|pair| pair.len() == 2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+fn is_path(s: &str) -> bool {
+    let s = if s.starts_with("::") { &s[2..] } else { s };
+    !s.is_empty() && s.split("::").all(|segment| {
+        segment.chars().next().map_or(false, |c| c.is_alphabetic() || c == '_') &&
+        segment.chars().all(|c| c.is_alphanumeric() || c == '_')
+    })
+}
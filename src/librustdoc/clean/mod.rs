@@ -41,6 +41,7 @@ use rustc::hir::print as pprust;
 use rustc::ty::subst::Substs;
 use rustc::ty::{self, AdtKind};
 use rustc::middle::stability;
+use rustc::session::config::ErrorOutputType;
 use rustc::util::nodemap::{FnvHashMap, FnvHashSet};
 
 use rustc::hir;
@@ -58,7 +59,9 @@ use visit_ast;
 use html::item_type::ItemType;
 
 pub mod inline;
+pub mod links;
 mod simplify;
+mod synthetic;
 
 // extract the stability index for a node from tcx, if possible
 fn get_stability(cx: &DocContext, def_id: DefId) -> Option<Stability> {
@@ -120,6 +123,11 @@ pub struct Crate {
     // These are later on moved into `CACHEKEY`, leaving the map empty.
     // Only here so that they can be filtered through the rustdoc passes.
     pub external_traits: FnvHashMap<DefId, Trait>,
+    /// The paths of the intra-doc links of each item that failed to resolve,
+    /// reported by the `lint-docs` pass.
+    pub unresolved_links: FnvHashMap<DefId, Vec<String>>,
+    /// The format the `lint-docs` pass reports its diagnostics in.
+    pub error_format: ErrorOutputType,
 }
 
 struct CrateNum(def_id::CrateNum);
@@ -223,6 +231,8 @@ impl<'a, 'tcx> Clean<Crate> for visit_ast::RustdocVisitor<'a, 'tcx> {
             primitives: primitives,
             access_levels: Arc::new(mem::replace(&mut access_levels, Default::default())),
             external_traits: mem::replace(&mut external_traits, Default::default()),
+            unresolved_links: FnvHashMap(),
+            error_format: ErrorOutputType::default(),
         }
     }
 }
@@ -426,6 +436,11 @@ impl Clean<Item> for doctree::Module {
         items.extend(self.macros.iter().map(|x| x.clean(cx)));
         items.extend(self.def_traits.iter().map(|x| x.clean(cx)));
 
+        let synthetic = items.iter().flat_map(|item| {
+            synthetic::synthesize_impls(cx, item)
+        }).collect::<Vec<_>>();
+        items.extend(synthetic);
+
         // determine if we should display the inner contents or
         // the outer `mod` item for the source code.
         let whence = {
@@ -2369,6 +2384,12 @@ pub struct Impl {
     pub for_: Type,
     pub items: Vec<Item>,
     pub polarity: Option<ImplPolarity>,
+    /// Whether this is an auto trait impl computed by rustdoc rather than
+    /// written in the source.
+    pub synthetic: bool,
+    /// For a blanket impl shown on one of the types it applies to, the self
+    /// type the impl is written for (e.g. `T`).
+    pub blanket_impl: Option<Type>,
 }
 
 impl Clean<Vec<Item>> for doctree::Impl {
@@ -2408,6 +2429,8 @@ impl Clean<Vec<Item>> for doctree::Impl {
                 for_: self.for_.clean(cx),
                 items: items,
                 polarity: Some(self.polarity.clean(cx)),
+                synthetic: false,
+                blanket_impl: None,
             }),
         });
         ret
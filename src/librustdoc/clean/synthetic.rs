@@ -0,0 +1,281 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Trait implementations that a type has without an `impl` block naming it:
+//! auto traits such as `Send` and `Sync`, and blanket impls such as
+//! `impl<T: Display> ToString for T`. Both are found by asking trait
+//! selection about each local struct, enum and union.
+
+use rustc::hir;
+use rustc::hir::def_id::DefId;
+use rustc::infer::TypeOrigin;
+use rustc::traits::{self, ObligationCause, Reveal, SelectionContext};
+use rustc::ty::subst::{Subst, Substs};
+use rustc::ty::{self, Ty, TyCtxt, ToPredicate};
+use rustc::util::nodemap::FnvHashSet;
+use syntax_pos::DUMMY_SP;
+
+use core::DocContext;
+
+use super::{Clean, Item, Impl, ImplPolarity, Span, Type, Inherited};
+use super::{ImplItem, StructItem, UnionItem, EnumItem, TyMethodItem, MethodItem};
+use super::{TraitBound, RegionBound, Method};
+
+/// Returns the auto trait and blanket impls of `item`, if it is a local
+/// struct, enum or union.
+pub fn synthesize_impls(cx: &DocContext, item: &Item) -> Vec<Item> {
+    match item.inner {
+        StructItem(..) | UnionItem(..) | EnumItem(..) => {}
+        _ => return vec![],
+    }
+    let tcx = match cx.tcx_opt() {
+        Some(tcx) => tcx,
+        None => return vec![],
+    };
+    let node_id = match tcx.map.as_local_node_id(item.def_id) {
+        Some(id) => id,
+        None => return vec![],
+    };
+    let param_env = ty::ParameterEnvironment::for_item(tcx, node_id);
+
+    let mut impls = auto_trait_impls(cx, tcx, &param_env, item.def_id);
+    impls.extend(blanket_impls(cx, tcx, &param_env, item.def_id));
+    impls
+}
+
+fn auto_trait_impls<'a, 'tcx>(cx: &DocContext,
+                              tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                              param_env: &ty::ParameterEnvironment<'tcx>,
+                              did: DefId) -> Vec<Item> {
+    let scheme = tcx.lookup_item_type(did);
+    let ty = scheme.ty.subst(tcx, param_env.free_substs);
+    let auto_traits = [tcx.lang_items.send_trait(), tcx.lang_items.sync_trait()];
+
+    auto_traits.iter().filter_map(|&t| t).filter(|&trait_did| {
+        !has_explicit_impl(tcx, trait_did, did)
+    }).map(|trait_did| {
+        let mut predicates = tcx.lookup_predicates(did);
+        let polarity = match auto_trait_bounds(tcx, param_env, scheme.generics,
+                                               trait_did, ty) {
+            Some(bounds) => {
+                predicates.predicates.extend(bounds);
+                ImplPolarity::Positive
+            }
+            None => ImplPolarity::Negative,
+        };
+        let trait_ref = ty::TraitRef {
+            def_id: trait_did,
+            substs: Substs::new_trait(tcx, scheme.ty, &[]),
+        };
+        Item {
+            name: None,
+            attrs: vec![],
+            source: Span::empty(),
+            visibility: Some(Inherited),
+            stability: None,
+            deprecation: None,
+            def_id: did,
+            inner: ImplItem(Impl {
+                unsafety: hir::Unsafety::Normal,
+                generics: (scheme.generics, &predicates).clean(cx),
+                provided_trait_methods: FnvHashSet(),
+                trait_: Some(trait_path(cx, trait_ref)),
+                for_: scheme.ty.clean(cx),
+                items: vec![],
+                polarity: Some(polarity),
+                synthetic: true,
+                blanket_impl: None,
+            }),
+        }
+    }).collect()
+}
+
+/// Returns whether the crate graph has an impl of `trait_did` written for
+/// the type `did`, in which case it is documented like any other impl.
+fn has_explicit_impl<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                               trait_did: DefId,
+                               did: DefId) -> bool {
+    let mut found = false;
+    tcx.lookup_trait_def(trait_did).for_each_impl(tcx, |impl_did| {
+        if let Some(trait_ref) = tcx.impl_trait_ref(impl_did) {
+            found |= trait_ref.self_ty().ty_to_def_id() == Some(did);
+        }
+    });
+    found
+}
+
+/// Finds the `P: Trait` bounds on the type parameters of `ty` that it needs
+/// to implement the auto trait `trait_did`. Returns `None` if it does not
+/// implement it whatever the parameters are.
+fn auto_trait_bounds<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                               param_env: &ty::ParameterEnvironment<'tcx>,
+                               generics: &ty::Generics<'tcx>,
+                               trait_did: DefId,
+                               ty: Ty<'tcx>) -> Option<Vec<ty::Predicate<'tcx>>> {
+    let mut bounds = generics.types.iter().map(|def| {
+        ty::TraitRef {
+            def_id: trait_did,
+            substs: Substs::new_trait(tcx, tcx.mk_param_from_def(def), &[]),
+        }.to_predicate()
+    }).collect::<Vec<_>>();
+    if !implements(tcx, param_env, &bounds, trait_did, ty) {
+        return None;
+    }
+
+    // Drop each bound the impl still holds without, leaving a minimal set.
+    let mut i = 0;
+    while i < bounds.len() {
+        let mut without = bounds.clone();
+        without.remove(i);
+        if implements(tcx, param_env, &without, trait_did, ty) {
+            bounds = without;
+        } else {
+            i += 1;
+        }
+    }
+    Some(bounds)
+}
+
+fn implements<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                        param_env: &ty::ParameterEnvironment<'tcx>,
+                        bounds: &[ty::Predicate<'tcx>],
+                        trait_did: DefId,
+                        ty: Ty<'tcx>) -> bool {
+    let mut caller_bounds = param_env.caller_bounds.clone();
+    caller_bounds.extend(bounds.iter().cloned());
+    let param_env = param_env.with_caller_bounds(caller_bounds);
+    tcx.infer_ctxt(None, Some(param_env), Reveal::NotSpecializable).enter(|infcx| {
+        let obligation = infcx.tcx.predicate_for_trait_def(ObligationCause::dummy(),
+                                                           trait_did, 0, ty, &[]);
+        SelectionContext::new(&infcx).evaluate_obligation_conservatively(&obligation)
+    })
+}
+
+fn blanket_impls<'a, 'tcx>(cx: &DocContext,
+                           tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                           param_env: &ty::ParameterEnvironment<'tcx>,
+                           did: DefId) -> Vec<Item> {
+    let scheme = tcx.lookup_item_type(did);
+    let ty = scheme.ty.subst(tcx, param_env.free_substs);
+
+    blanket_impl_dids(cx, tcx).into_iter().filter(|&impl_did| {
+        blanket_impl_applies(tcx, param_env, impl_did, ty)
+    }).map(|impl_did| {
+        let trait_ref = tcx.impl_trait_ref(impl_did).unwrap();
+        let impl_scheme = tcx.lookup_item_type(impl_did);
+        let predicates = tcx.lookup_predicates(impl_did);
+        let provided = tcx.provided_trait_methods(trait_ref.def_id)
+                          .into_iter()
+                          .map(|meth| meth.name.to_string())
+                          .collect();
+        Item {
+            name: None,
+            attrs: vec![],
+            source: Span::empty(),
+            visibility: Some(Inherited),
+            stability: tcx.lookup_stability(impl_did).clean(cx),
+            deprecation: tcx.lookup_deprecation(impl_did).clean(cx),
+            def_id: impl_did,
+            inner: ImplItem(Impl {
+                unsafety: hir::Unsafety::Normal,
+                generics: (impl_scheme.generics, &predicates).clean(cx),
+                provided_trait_methods: provided,
+                trait_: Some(trait_path(cx, trait_ref)),
+                for_: scheme.ty.clean(cx),
+                items: impl_items(cx, tcx, impl_did),
+                polarity: Some(ImplPolarity::Positive),
+                synthetic: false,
+                blanket_impl: Some(trait_ref.self_ty().clean(cx)),
+            }),
+        }
+    }).collect()
+}
+
+/// Returns every trait impl in the crate graph whose self type is a bare
+/// type parameter and whose trait is documented. The list is built on first
+/// use and kept in the `DocContext`.
+fn blanket_impl_dids<'a, 'tcx>(cx: &DocContext,
+                               tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Vec<DefId> {
+    if let Some(ref dids) = *cx.blanket_impls.borrow() {
+        return dids.clone();
+    }
+
+    let mut dids = tcx.sess.cstore.implementations_of_trait(None);
+    dids.retain(|&did| !tcx.sess.cstore.is_default_impl(did));
+    dids.extend(tcx.map.krate().items.values().filter_map(|item| {
+        match item.node {
+            hir::ItemImpl(_, _, _, Some(_), ..) => Some(tcx.map.local_def_id(item.id)),
+            _ => None,
+        }
+    }));
+    dids.retain(|&did| {
+        let trait_ref = match tcx.impl_trait_ref(did) {
+            Some(trait_ref) => trait_ref,
+            None => return false,
+        };
+        let is_blanket = match trait_ref.self_ty().sty {
+            ty::TyParam(..) => true,
+            _ => false,
+        };
+        is_blanket && tcx.trait_impl_polarity(did) == hir::ImplPolarity::Positive &&
+            cx.access_levels.borrow().is_doc_reachable(trait_ref.def_id)
+    });
+
+    *cx.blanket_impls.borrow_mut() = Some(dids.clone());
+    dids
+}
+
+/// Unifies the self type of the blanket impl `impl_did` with `ty` and checks
+/// that the impl's where-clauses may hold in the type's environment.
+fn blanket_impl_applies<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                  param_env: &ty::ParameterEnvironment<'tcx>,
+                                  impl_did: DefId,
+                                  ty: Ty<'tcx>) -> bool {
+    tcx.infer_ctxt(None, Some(param_env.clone()), Reveal::NotSpecializable).enter(|infcx| {
+        let substs = infcx.fresh_substs_for_item(DUMMY_SP, impl_did);
+        let trait_ref = tcx.impl_trait_ref(impl_did).unwrap().subst(infcx.tcx, substs);
+        let origin = TypeOrigin::Misc(DUMMY_SP);
+        if infcx.eq_types(false, origin, trait_ref.self_ty(), ty).is_err() {
+            return false;
+        }
+        let predicates = tcx.lookup_predicates(impl_did).instantiate(infcx.tcx, substs);
+        let mut selcx = SelectionContext::new(&infcx);
+        traits::predicates_for_generics(ObligationCause::dummy(), &predicates)
+            .iter()
+            .all(|obligation| selcx.evaluate_obligation(obligation))
+    })
+}
+
+fn impl_items<'a, 'tcx>(cx: &DocContext,
+                        tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                        impl_did: DefId) -> Vec<Item> {
+    tcx.impl_or_trait_items(impl_did).iter().map(|&did| {
+        let mut item = tcx.impl_or_trait_item(did).clean(cx);
+        // Methods of impls come out of `ty::Method` as required methods.
+        item.inner = match item.inner {
+            TyMethodItem(m) => MethodItem(Method {
+                unsafety: m.unsafety,
+                constness: hir::Constness::NotConst,
+                decl: m.decl,
+                generics: m.generics,
+                abi: m.abi,
+            }),
+            inner => inner,
+        };
+        item
+    }).collect()
+}
+
+fn trait_path(cx: &DocContext, trait_ref: ty::TraitRef) -> Type {
+    match trait_ref.clean(cx) {
+        TraitBound(polyt, _) => polyt.trait_,
+        RegionBound(..) => unreachable!(),
+    }
+}
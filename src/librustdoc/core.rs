@@ -27,7 +27,9 @@ use rustc_metadata::cstore::CStore;
 use syntax::{ast, codemap};
 use syntax::feature_gate::UnstableFeatures;
 use errors;
-use errors::emitter::ColorConfig;
+use errors::emitter::{Emitter, EmitterWriter};
+use syntax::json::JsonEmitter;
+use syntax::sarif::SarifEmitter;
 
 use std::cell::{RefCell, Cell};
 use std::mem;
@@ -36,8 +38,9 @@ use std::path::PathBuf;
 
 use visit_ast::RustdocVisitor;
 use clean;
-use clean::Clean;
+use clean::{Attributes, Clean};
 use html::render::RenderInfo;
+use span_map;
 
 pub use rustc::session::config::Input;
 pub use rustc::session::search_paths::SearchPaths;
@@ -50,6 +53,10 @@ pub enum MaybeTyped<'a, 'tcx: 'a> {
 
 pub type ExternalPaths = FnvHashMap<DefId, (Vec<String>, clean::TypeKind)>;
 
+/// The resolved intra-doc links of each item: the link's label, the item it
+/// points to and the anchor within that item's page, if any.
+pub type IntraDocLinks = FnvHashMap<DefId, Vec<(String, DefId, Option<String>)>>;
+
 pub struct DocContext<'a, 'tcx: 'a> {
     pub map: &'a hir_map::Map<'tcx>,
     pub maybe_typed: MaybeTyped<'a, 'tcx>,
@@ -66,6 +73,8 @@ pub struct DocContext<'a, 'tcx: 'a> {
     pub renderinfo: RefCell<RenderInfo>,
     /// Later on moved through `clean::Crate` into `html::render::CACHE_KEY`
     pub external_traits: RefCell<FnvHashMap<DefId, clean::Trait>>,
+    /// Blanket trait impls from the whole crate graph, collected on first use
+    pub blanket_impls: RefCell<Option<Vec<DefId>>>,
 
     // The current set of type and lifetime substitutions,
     // for expanding type aliases at the HIR level:
@@ -124,12 +133,31 @@ impl DocAccessLevels for AccessLevels<DefId> {
 }
 
 
+/// Creates a handler printing diagnostics to stderr in the format given with
+/// `--error-format`.
+pub fn new_handler(error_format: config::ErrorOutputType,
+                   codemap: Rc<codemap::CodeMap>) -> errors::Handler {
+    let emitter: Box<Emitter> = match error_format {
+        config::ErrorOutputType::HumanReadable(color_config) => {
+            Box::new(EmitterWriter::stderr(color_config, Some(codemap)))
+        }
+        config::ErrorOutputType::Short(color_config) => {
+            Box::new(EmitterWriter::stderr_short(color_config, Some(codemap)))
+        }
+        config::ErrorOutputType::Json => Box::new(JsonEmitter::stderr(None, codemap)),
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::stderr(None, codemap)),
+    };
+    errors::Handler::with_emitter(true, false, emitter)
+}
+
 pub fn run_core(search_paths: SearchPaths,
                 cfgs: Vec<String>,
                 externs: config::Externs,
                 input: Input,
                 triple: Option<String>,
-                maybe_sysroot: Option<PathBuf>) -> (clean::Crate, RenderInfo)
+                maybe_sysroot: Option<PathBuf>,
+                error_format: config::ErrorOutputType,
+                render_sources: bool) -> (clean::Crate, RenderInfo)
 {
     // Parse, resolve, and typecheck the given crate.
 
@@ -150,14 +178,12 @@ pub fn run_core(search_paths: SearchPaths,
         target_triple: triple.unwrap_or(config::host_triple().to_string()),
         // Ensure that rustdoc works even if rustc is feature-staged
         unstable_features: UnstableFeatures::Allow,
+        error_format: error_format,
         ..config::basic_options().clone()
     };
 
     let codemap = Rc::new(codemap::CodeMap::new());
-    let diagnostic_handler = errors::Handler::with_tty_emitter(ColorConfig::Auto,
-                                                               true,
-                                                               false,
-                                                               Some(codemap.clone()));
+    let diagnostic_handler = new_handler(error_format, codemap.clone());
 
     let dep_graph = DepGraph::new(false);
     let _ignore = dep_graph.in_ignore();
@@ -212,6 +238,7 @@ pub fn run_core(search_paths: SearchPaths,
             deref_mut_trait_did: Cell::new(None),
             access_levels: RefCell::new(access_levels),
             external_traits: Default::default(),
+            blanket_impls: Default::default(),
             renderinfo: Default::default(),
             ty_substs: Default::default(),
             lt_substs: Default::default(),
@@ -223,6 +250,15 @@ pub fn run_core(search_paths: SearchPaths,
             v.visit(ctxt.map.krate());
             v.clean(&ctxt)
         };
+        let krate = clean::links::collect_intra_doc_links(&ctxt, krate);
+        // The span map is only used to render the source pages, which a crate
+        // can opt out of.
+        let no_source = krate.module.as_ref().map_or(false, |m| {
+            m.attrs.list("doc").has_word("html_no_source")
+        });
+        if render_sources && !no_source {
+            ctxt.renderinfo.borrow_mut().span_map = span_map::collect(tcx);
+        }
 
         (krate, ctxt.renderinfo.into_inner())
     }), &sess)
@@ -0,0 +1,153 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Documentation coverage reports, printed by `--show-coverage`.
+//!
+//! The report covers the items that remain in the cleaned crate after the
+//! passes have run, which by default are the public ones. Items of inherent
+//! impls count towards the module the impl is in, while the items of trait
+//! impls aren't counted as they inherit the trait's documentation.
+
+use std::cmp;
+use std::collections::BTreeMap;
+use std::iter::repeat;
+use std::ops;
+
+use serialize::json::as_json;
+
+use clean::{self, Item};
+use html::markdown;
+
+#[derive(Default, Clone, Copy, RustcEncodable)]
+struct ItemCount {
+    total: u64,
+    with_docs: u64,
+    with_examples: u64,
+}
+
+impl ItemCount {
+    fn count_item(&mut self, item: &Item) {
+        self.total += 1;
+        if let Some(doc) = item.doc_value() {
+            if !doc.trim().is_empty() {
+                self.with_docs += 1;
+            }
+            if markdown::has_code_example(doc) {
+                self.with_examples += 1;
+            }
+        }
+    }
+
+    fn percentage(&self, count: u64) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            count as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+
+impl ops::AddAssign for ItemCount {
+    fn add_assign(&mut self, rhs: ItemCount) {
+        self.total += rhs.total;
+        self.with_docs += rhs.with_docs;
+        self.with_examples += rhs.with_examples;
+    }
+}
+
+#[derive(RustcEncodable)]
+struct Coverage {
+    /// The counts of each module, keyed by the module's path.
+    modules: BTreeMap<String, ItemCount>,
+    total: ItemCount,
+}
+
+/// Prints the documentation coverage of `krate` to stdout, either as a table
+/// or as JSON.
+pub fn run(krate: &clean::Crate, json: bool) {
+    let mut modules = BTreeMap::new();
+    if let Some(ref module) = krate.module {
+        visit(module, &krate.name, &mut modules);
+    }
+    let mut total = ItemCount::default();
+    for count in modules.values() {
+        total += *count;
+    }
+    let coverage = Coverage { modules: modules, total: total };
+
+    if json {
+        println!("{}", as_json(&coverage));
+    } else {
+        print_table(&coverage);
+    }
+}
+
+fn visit(item: &Item, module: &str, modules: &mut BTreeMap<String, ItemCount>) {
+    let children: &[Item] = match item.inner {
+        clean::StrippedItem(..) | clean::ImportItem(..) | clean::ExternCrateItem(..) |
+        clean::PrimitiveItem(..) => return,
+        clean::ImplItem(ref i) => {
+            // Impls themselves aren't counted, only the items of inherent ones.
+            if i.trait_.is_none() {
+                for child in &i.items {
+                    visit(child, module, modules);
+                }
+            }
+            return
+        }
+        clean::ModuleItem(ref m) => {
+            // The crate root is unnamed and its path is the crate's name.
+            let path = match item.name {
+                Some(ref name) if !item.is_crate() => format!("{}::{}", module, name),
+                _ => module.to_string(),
+            };
+            modules.entry(path.clone()).or_insert_with(ItemCount::default).count_item(item);
+            for child in &m.items {
+                visit(child, &path, modules);
+            }
+            return
+        }
+        clean::StructItem(ref s) => &s.fields,
+        clean::UnionItem(ref u) => &u.fields,
+        clean::EnumItem(ref e) => &e.variants,
+        clean::VariantItem(clean::Variant { kind: clean::StructVariant(ref v) }) => &v.fields,
+        clean::TraitItem(ref t) => &t.items,
+        _ => &[],
+    };
+    modules.entry(module.to_string()).or_insert_with(ItemCount::default).count_item(item);
+    for child in children {
+        visit(child, module, modules);
+    }
+}
+
+fn print_table(coverage: &Coverage) {
+    let width = coverage.modules.keys().map(|name| name.len()).fold(20, cmp::max);
+    let row = |name: &str, count: &ItemCount| {
+        println!("| {:<width$} | {:>10} | {:>9.1}% | {:>10} | {:>9.1}% |",
+                 name,
+                 count.with_docs, count.percentage(count.with_docs),
+                 count.with_examples, count.percentage(count.with_examples),
+                 width = width);
+    };
+    let separator = format!("+-{}-+------------+------------+------------+------------+",
+                            repeat("-").take(width).collect::<String>());
+
+    println!("{}", separator);
+    println!("| {:<width$} | {:>10} | {:>10} | {:>10} | {:>10} |",
+             "Module", "Documented", "Percentage", "Examples", "Percentage",
+             width = width);
+    println!("{}", separator);
+    for (name, count) in &coverage.modules {
+        row(name, count);
+    }
+    println!("{}", separator);
+    row("Total", &coverage.total);
+    println!("{}", separator);
+}
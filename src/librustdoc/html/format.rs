@@ -600,7 +600,11 @@ fn fmt_impl(i: &clean::Impl, f: &mut fmt::Formatter, link_trait: bool) -> fmt::R
         }
         write!(f, " for ")?;
     }
-    write!(f, "{}{}", i.for_, WhereClause(&i.generics))?;
+    if let Some(ref ty) = i.blanket_impl {
+        write!(f, "{}{}", ty, WhereClause(&i.generics))?;
+    } else {
+        write!(f, "{}{}", i.for_, WhereClause(&i.generics))?;
+    }
     Ok(())
 }
 
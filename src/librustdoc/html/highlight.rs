@@ -30,7 +30,7 @@ use syntax::codemap::CodeMap;
 use syntax::parse::lexer::{self, Reader, TokenAndSpan};
 use syntax::parse::token;
 use syntax::parse;
-use syntax_pos::Span;
+use syntax_pos::{BytePos, Pos, Span};
 
 /// Highlights `src`, returning the HTML output.
 pub fn render_with_highlighting(src: &str, class: Option<&str>, id: Option<&str>,
@@ -55,6 +55,31 @@ pub fn render_with_highlighting(src: &str, class: Option<&str>, id: Option<&str>
     String::from_utf8_lossy(&out[..]).into_owned()
 }
 
+/// Highlights `src` like `render_with_highlighting`, also turning the tokens
+/// found in `links` into links. `links` holds byte ranges of `src` along with
+/// the URLs they link to, sorted by position.
+pub fn render_with_links(src: &str, links: &[(usize, usize, String)]) -> String {
+    let sess = parse::ParseSess::new();
+    let fm = sess.codemap().new_filemap("<stdin>".to_string(), None, src.to_string());
+
+    let mut out = LinkWriter {
+        out: Vec::new(),
+        links: links,
+        next: 0,
+        start: fm.start_pos,
+    };
+    write_header(None, None, &mut out.out).unwrap();
+
+    let mut classifier = Classifier::new(lexer::StringReader::new(&sess.span_diagnostic, fm),
+                                         sess.codemap());
+    if let Err(_) = classifier.write_source(&mut out) {
+        return format!("<pre>{}</pre>", src);
+    }
+
+    write_footer(&mut out.out).unwrap();
+    String::from_utf8_lossy(&out.out[..]).into_owned()
+}
+
 /// Highlights `src`, returning the HTML output. Returns only the inner html to
 /// be inserted into an element. C.f., `render_with_highlighting` which includes
 /// an enclosing `<pre>` block.
@@ -158,6 +183,55 @@ impl<U: Write> Writer for U {
     }
 }
 
+/// A `Writer` wrapping the tokens at given positions in links.
+struct LinkWriter<'a> {
+    out: Vec<u8>,
+    links: &'a [(usize, usize, String)],
+    /// Index of the first link that could start at or after the next token.
+    next: usize,
+    start: BytePos,
+}
+
+impl<'a> Writer for LinkWriter<'a> {
+    fn string<T: Display>(&mut self,
+                          text: T,
+                          klass: Class,
+                          tas: Option<&TokenAndSpan>)
+                          -> io::Result<()> {
+        let links = self.links;
+        let link = match tas {
+            Some(tas) => {
+                let lo = (tas.sp.lo - self.start).to_usize();
+                let hi = (tas.sp.hi - self.start).to_usize();
+                while self.next < links.len() && links[self.next].0 < lo {
+                    self.next += 1;
+                }
+                match links.get(self.next) {
+                    Some(&(l, h, ref url)) if l == lo && h == hi => Some(url),
+                    _ => None,
+                }
+            }
+            None => None,
+        };
+        match link {
+            Some(url) => {
+                write!(self.out, "<a href=\"{}\">", url)?;
+                self.out.string(text, klass, tas)?;
+                write!(self.out, "</a>")
+            }
+            None => self.out.string(text, klass, tas),
+        }
+    }
+
+    fn enter_span(&mut self, klass: Class) -> io::Result<()> {
+        self.out.enter_span(klass)
+    }
+
+    fn exit_span(&mut self) -> io::Result<()> {
+        self.out.exit_span()
+    }
+}
+
 impl<'a> Classifier<'a> {
     pub fn new(lexer: lexer::StringReader<'a>, codemap: &'a CodeMap) -> Classifier<'a> {
         Classifier {
@@ -12,6 +12,7 @@ use std::fmt;
 use std::io;
 
 use externalfiles::ExternalHtml;
+use html::escape::Escape;
 
 #[derive(Clone)]
 pub struct Layout {
@@ -20,6 +21,9 @@ pub struct Layout {
     pub external_html: ExternalHtml,
     pub krate: String,
     pub playground_url: String,
+    /// Names of the themes offered by the theme picker, the first one being
+    /// the default.
+    pub themes: Vec<String>,
 }
 
 pub struct Page<'a> {
@@ -48,7 +52,9 @@ r##"<!DOCTYPE html>
     <title>{title}</title>
 
     <link rel="stylesheet" type="text/css" href="{root_path}rustdoc.css">
-    <link rel="stylesheet" type="text/css" href="{root_path}main.css">
+    <link rel="stylesheet" type="text/css" href="{root_path}main.css" id="themeStyle"
+          data-themes="{themes}">
+    <script src="{root_path}storage.js"></script>
     {css_extension}
 
     {favicon}
@@ -70,6 +76,10 @@ r##"<!DOCTYPE html>
     </nav>
 
     <nav class="sub">
+        <div class="theme-picker js-only">
+            <button id="theme-picker" title="Pick another theme">Theme</button>
+            <div id="theme-choices">{theme_choices}</div>
+        </div>
         <form class="search-form js-only">
             <div class="search-container">
                 <input class="search-input" name="search"
@@ -120,12 +130,20 @@ r##"<!DOCTYPE html>
                     Accepted types are: <code>fn</code>, <code>mod</code>,
                     <code>struct</code>, <code>enum</code>,
                     <code>trait</code>, <code>type</code>, <code>macro</code>,
-                    and <code>const</code>.
+                    <code>const</code>, and any other kind of item, such as
+                    <code>method</code>, <code>variant</code> or
+                    <code>structfield</code>.
                 </p>
 
                 <p>
                     Search functions by type signature (e.g.
-                    <code>vec -> usize</code> or <code>* -> vec</code>)
+                    <code>vec -> usize</code>, <code>Vec&lt;T&gt; -> usize</code>
+                    or <code>* -> vec</code>)
+                </p>
+
+                <p>
+                    Items whose documentation contains all the words searched
+                    for are listed after the items matching by name.
                 </p>
             </div>
         </div>
@@ -141,7 +159,6 @@ r##"<!DOCTYPE html>
     <script src="{root_path}jquery.js"></script>
     <script src="{root_path}main.js"></script>
     {play_js}
-    <script defer src="{root_path}search-index.js"></script>
 </body>
 </html>"##,
     css_extension = if css_file_extension {
@@ -175,6 +192,10 @@ r##"<!DOCTYPE html>
     sidebar   = *sidebar,
     krate     = layout.krate,
     play_url  = layout.playground_url,
+    themes    = Escape(&layout.themes.join(",")),
+    theme_choices = layout.themes.iter()
+                          .map(|theme| format!("<button>{}</button>", Escape(theme)))
+                          .collect::<String>(),
     play_js   = if layout.playground_url.is_empty() {
         "".to_string()
     } else {
@@ -29,12 +29,13 @@
 use libc;
 use std::ascii::AsciiExt;
 use std::cell::RefCell;
+use std::cmp;
 use std::default::Default;
 use std::ffi::CString;
 use std::fmt;
 use std::slice;
 use std::str;
-use syntax::feature_gate::UnstableFeatures;
+use syntax::util::lev_distance::lev_distance;
 
 use html::render::derive_id;
 use html::toc::TocBuilder;
@@ -49,6 +50,10 @@ pub struct Markdown<'a>(pub &'a str);
 /// A unit struct like `Markdown`, that renders the markdown with a
 /// table of contents.
 pub struct MarkdownWithToc<'a>(pub &'a str);
+/// A unit struct like `Markdown`, that also resolves the given intra-doc
+/// links. Each link is a reference label used in the markdown along with the
+/// URL it should point to.
+pub struct MarkdownWithLinks<'a>(pub &'a str, pub &'a [(String, String)]);
 
 const DEF_OUNIT: libc::size_t = 64;
 const HOEDOWN_EXT_NO_INTRA_EMPHASIS: libc::c_uint = 1 << 11;
@@ -142,10 +147,11 @@ struct html_toc_data {
     nesting_level: libc::c_int,
 }
 
-struct MyOpaque {
+struct MyOpaque<'a> {
     dfltblk: extern "C" fn(*mut hoedown_buffer, *const hoedown_buffer,
                            *const hoedown_buffer, *const hoedown_renderer_data),
     toc_builder: Option<TocBuilder>,
+    langs: CodeBlockLangs<'a>,
 }
 
 #[repr(C)]
@@ -225,7 +231,7 @@ pub fn render(w: &mut fmt::Formatter, s: &str, print_toc: bool) -> fmt::Result {
             if orig_text.is_null() { return }
 
             let opaque = (*data).opaque as *mut hoedown_html_renderer_state;
-            let my_opaque: &MyOpaque = &*((*opaque).opaque as *const MyOpaque);
+            let my_opaque: &mut MyOpaque = &mut *((*opaque).opaque as *mut MyOpaque);
             let text = (*orig_text).as_bytes();
             let origtext = str::from_utf8(text).unwrap();
             debug!("docblock: ==============\n{:?}\n=======", text);
@@ -234,7 +240,7 @@ pub fn render(w: &mut fmt::Formatter, s: &str, print_toc: bool) -> fmt::Result {
             } else {
                 let rlang = (*lang).as_bytes();
                 let rlang = str::from_utf8(rlang).unwrap();
-                if !LangString::parse(rlang).rust {
+                if !LangString::parse(my_opaque.langs.full_lang(rlang)).rust {
                     (my_opaque.dfltblk)(ob, orig_text, lang,
                                         opaque as *const hoedown_renderer_data);
                     true
@@ -355,7 +361,8 @@ pub fn render(w: &mut fmt::Formatter, s: &str, print_toc: bool) -> fmt::Result {
         let renderer = hoedown_html_renderer_new(0, 0);
         let mut opaque = MyOpaque {
             dfltblk: (*renderer).blockcode.unwrap(),
-            toc_builder: if print_toc {Some(TocBuilder::new())} else {None}
+            toc_builder: if print_toc {Some(TocBuilder::new())} else {None},
+            langs: CodeBlockLangs::new(s),
         };
         (*((*renderer).opaque as *mut hoedown_html_renderer_state)).opaque
                 = &mut opaque as *mut _ as *mut libc::c_void;
@@ -383,6 +390,41 @@ pub fn render(w: &mut fmt::Formatter, s: &str, print_toc: bool) -> fmt::Result {
     }
 }
 
+/// The language strings of the fenced code blocks of a document, which
+/// hoedown cuts at the first whitespace before handing them to a renderer.
+struct CodeBlockLangs<'a> {
+    langs: Vec<&'a str>,
+    next: usize,
+}
+
+impl<'a> CodeBlockLangs<'a> {
+    fn new(md: &'a str) -> CodeBlockLangs<'a> {
+        CodeBlockLangs {
+            langs: code_block_langs(md),
+            next: 0,
+        }
+    }
+
+    /// Returns the full language string of the next code block, whose
+    /// language string as seen by hoedown is `lang`.
+    fn full_lang<'b>(&mut self, lang: &'b str) -> &'b str where 'a: 'b {
+        let pos = self.langs[self.next..].iter().position(|l| l.starts_with(lang));
+        match pos {
+            Some(pos) => {
+                self.next += pos + 1;
+                self.langs[self.next - 1]
+            }
+            None => lang,
+        }
+    }
+}
+
+/// The state of `find_testable_code` while hoedown renders the document.
+struct TestableCode<'a, 'b> {
+    tests: &'a mut ::test::Collector,
+    langs: CodeBlockLangs<'b>,
+}
+
 pub fn find_testable_code(doc: &str, tests: &mut ::test::Collector) {
     extern fn block(_ob: *mut hoedown_buffer,
                     text: *const hoedown_buffer,
@@ -390,26 +432,35 @@ pub fn find_testable_code(doc: &str, tests: &mut ::test::Collector) {
                     data: *const hoedown_renderer_data) {
         unsafe {
             if text.is_null() { return }
+            let opaque = (*data).opaque as *mut hoedown_html_renderer_state;
+            let state = &mut *((*opaque).opaque as *mut TestableCode);
             let block_info = if lang.is_null() {
                 LangString::all_false()
             } else {
                 let lang = (*lang).as_bytes();
                 let s = str::from_utf8(lang).unwrap();
-                LangString::parse(s)
+                LangString::parse(state.langs.full_lang(s))
             };
             if !block_info.rust { return }
+            if !block_info.invalid_cfgs.is_empty() {
+                let msg = format!("unsupported `cfg` in code block: {}; only names, \
+                                   `name = \"value\"` pairs and `all(...)` of those can \
+                                   be set for a single example",
+                                  block_info.invalid_cfgs.join(", "));
+                state.tests.add_failing_test(msg, block_info.ignore);
+                return
+            }
             let text = (*text).as_bytes();
-            let opaque = (*data).opaque as *mut hoedown_html_renderer_state;
-            let tests = &mut *((*opaque).opaque as *mut ::test::Collector);
             let text = str::from_utf8(text).unwrap();
             let lines = text.lines().map(|l| {
                 stripped_filtered_line(l).unwrap_or(l)
             });
             let text = lines.collect::<Vec<&str>>().join("\n");
-            tests.add_test(text.to_owned(),
-                           block_info.should_panic, block_info.no_run,
-                           block_info.ignore, block_info.test_harness,
-                           block_info.compile_fail, block_info.error_codes);
+            state.tests.add_test(text.to_owned(),
+                                 block_info.should_panic, block_info.no_run,
+                                 block_info.ignore, block_info.test_harness,
+                                 block_info.compile_fail, block_info.error_codes,
+                                 block_info.cfgs);
         }
     }
 
@@ -418,24 +469,29 @@ pub fn find_testable_code(doc: &str, tests: &mut ::test::Collector) {
                      level: libc::c_int, data: *const hoedown_renderer_data) {
         unsafe {
             let opaque = (*data).opaque as *mut hoedown_html_renderer_state;
-            let tests = &mut *((*opaque).opaque as *mut ::test::Collector);
+            let state = &mut *((*opaque).opaque as *mut TestableCode);
             if text.is_null() {
-                tests.register_header("", level as u32);
+                state.tests.register_header("", level as u32);
             } else {
                 let text = (*text).as_bytes();
                 let text = str::from_utf8(text).unwrap();
-                tests.register_header(text, level as u32);
+                state.tests.register_header(text, level as u32);
             }
         }
     }
 
+    let mut state = TestableCode {
+        tests: tests,
+        langs: CodeBlockLangs::new(doc),
+    };
+
     unsafe {
         let ob = hoedown_buffer_new(DEF_OUNIT);
         let renderer = hoedown_html_renderer_new(0, 0);
         (*renderer).blockcode = Some(block);
         (*renderer).header = Some(header);
         (*((*renderer).opaque as *mut hoedown_html_renderer_state)).opaque
-                = tests as *mut _ as *mut libc::c_void;
+                = &mut state as *mut _ as *mut libc::c_void;
 
         let document = hoedown_document_new(renderer, HOEDOWN_EXTENSIONS, 16);
         hoedown_document_render(document, ob, doc.as_ptr(),
@@ -447,6 +503,11 @@ pub fn find_testable_code(doc: &str, tests: &mut ::test::Collector) {
     }
 }
 
+/// The attributes rustdoc understands in the language string of a code block.
+const CODE_BLOCK_ATTRIBUTES: &'static [&'static str] = &[
+    "should_panic", "no_run", "ignore", "rust", "test_harness", "compile_fail",
+];
+
 #[derive(Eq, PartialEq, Clone, Debug)]
 struct LangString {
     should_panic: bool,
@@ -456,6 +517,8 @@ struct LangString {
     test_harness: bool,
     compile_fail: bool,
     error_codes: Vec<String>,
+    cfgs: Vec<String>,
+    invalid_cfgs: Vec<String>,
 }
 
 impl LangString {
@@ -468,6 +531,8 @@ impl LangString {
             test_harness: false,
             compile_fail: false,
             error_codes: Vec::new(),
+            cfgs: Vec::new(),
+            invalid_cfgs: Vec::new(),
         }
     }
 
@@ -475,11 +540,12 @@ impl LangString {
         let mut seen_rust_tags = false;
         let mut seen_other_tags = false;
         let mut data = LangString::all_false();
-        let mut allow_compile_fail = false;
-        let mut allow_error_code_check = false;
-        if UnstableFeatures::from_environment().is_nightly_build() {
-            allow_compile_fail = true;
-            allow_error_code_check = true;
+
+        let (cfgs, invalid_cfgs, string) = split_cfgs(string);
+        if !cfgs.is_empty() || !invalid_cfgs.is_empty() {
+            data.cfgs = cfgs;
+            data.invalid_cfgs = invalid_cfgs;
+            seen_rust_tags = true;
         }
 
         let tokens = string.split(|c: char|
@@ -494,18 +560,14 @@ impl LangString {
                 "ignore" => { data.ignore = true; seen_rust_tags = true; },
                 "rust" => { data.rust = true; seen_rust_tags = true; },
                 "test_harness" => { data.test_harness = true; seen_rust_tags = true; },
-                "compile_fail" if allow_compile_fail => {
+                "compile_fail" => {
                     data.compile_fail = true;
                     seen_rust_tags = true;
                     data.no_run = true;
                 }
-                x if allow_error_code_check && x.starts_with("E") && x.len() == 5 => {
-                    if let Ok(_) = x[1..].parse::<u32>() {
-                        data.error_codes.push(x.to_owned());
-                        seen_rust_tags = true;
-                    } else {
-                        seen_other_tags = true;
-                    }
+                x if is_error_code(x) => {
+                    data.error_codes.push(x.to_owned());
+                    seen_rust_tags = true;
                 }
                 _ => { seen_other_tags = true }
             }
@@ -526,6 +588,23 @@ impl<'a> fmt::Display for Markdown<'a> {
     }
 }
 
+impl<'a> fmt::Display for MarkdownWithLinks<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let MarkdownWithLinks(md, links) = *self;
+        if links.is_empty() {
+            return fmt::Display::fmt(&Markdown(md), fmt)
+        }
+        // Hoedown resolves reference links against the reference definitions
+        // found anywhere in the document, so appending a definition for each
+        // link is enough to turn `[Foo]` into a link to `Foo`'s page.
+        let mut md = md.to_string();
+        for &(ref label, ref url) in links {
+            md.push_str(&format!("\n\n[{}]: {}", label, url));
+        }
+        render(fmt, &md, false)
+    }
+}
+
 impl<'a> fmt::Display for MarkdownWithToc<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         let MarkdownWithToc(md) = *self;
@@ -533,6 +612,152 @@ impl<'a> fmt::Display for MarkdownWithToc<'a> {
     }
 }
 
+/// Splits the `cfg(...)` attributes off the language string of a code block.
+/// Returns the ones made of `name`, `name = "value"` and `all(...)` in the
+/// format of `--cfg`, the ones that can't be expressed with `--cfg` (such as
+/// `cfg(any(...))`) as written, and the rest of the language string.
+fn split_cfgs(lang: &str) -> (Vec<String>, Vec<String>, String) {
+    let mut cfgs = Vec::new();
+    let mut invalid = Vec::new();
+    let mut rest = Vec::new();
+    for token in split_top_level(lang, |c| c == ',' || c.is_whitespace()) {
+        if !token.starts_with("cfg(") || !token.ends_with(')') {
+            rest.push(token);
+            continue
+        }
+        match parse_cfg_spec(&token[4..token.len() - 1]) {
+            Some(spec) => cfgs.extend(spec),
+            None => invalid.push(token.to_string()),
+        }
+    }
+    (cfgs, invalid, rest.join(","))
+}
+
+/// Parses the inside of a `cfg(...)` attribute into the equivalent `--cfg`
+/// options. Returns `None` if it isn't a name, a `name = "value"` pair or an
+/// `all(...)` of those.
+fn parse_cfg_spec(spec: &str) -> Option<Vec<String>> {
+    let mut cfgs = Vec::new();
+    for item in split_top_level(spec, |c| c == ',') {
+        let item = item.trim();
+        if item.starts_with("all(") && item.ends_with(')') {
+            match parse_cfg_spec(&item[4..item.len() - 1]) {
+                Some(spec) => cfgs.extend(spec),
+                None => return None,
+            }
+        } else if let Some(eq) = item.find('=') {
+            let (name, value) = (item[..eq].trim(), item[eq + 1..].trim());
+            if !is_cfg_name(name) || value.len() < 2 ||
+               !value.starts_with('"') || !value.ends_with('"') {
+                return None
+            }
+            cfgs.push(format!("{}={}", name, value));
+        } else if is_cfg_name(item) {
+            cfgs.push(item.to_string());
+        } else {
+            return None
+        }
+    }
+    if cfgs.is_empty() { None } else { Some(cfgs) }
+}
+
+/// Whether `name` can be the name of a `cfg` option.
+fn is_cfg_name(name: &str) -> bool {
+    name.chars().next().map_or(false, |c| c == '_' || c.is_alphabetic()) &&
+        name.chars().all(|c| c == '_' || c.is_alphanumeric())
+}
+
+/// Splits `s` at the characters matching `is_sep` that are neither inside
+/// parentheses nor inside a string literal, dropping the empty pieces.
+fn split_top_level<F: Fn(char) -> bool>(s: &str, is_sep: F) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string && depth > 0 => depth -= 1,
+            c if !in_string && depth == 0 && is_sep(c) => {
+                pieces.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    pieces.push(&s[start..]);
+    pieces.retain(|piece| !piece.is_empty());
+    pieces
+}
+
+/// Returns the language strings of the fenced code blocks of `md`.
+fn code_block_langs(md: &str) -> Vec<&str> {
+    let mut langs = Vec::new();
+    let mut in_code_block = false;
+    for line in md.lines() {
+        let line = line.trim_left();
+        if !line.starts_with("```") && !line.starts_with("~~~") {
+            continue
+        }
+        in_code_block = !in_code_block;
+        if in_code_block {
+            langs.push(line.trim_left_matches(|c| c == '`' || c == '~').trim());
+        }
+    }
+    langs
+}
+
+/// Whether `md` contains a code example, i.e. a fenced code block that is
+/// treated as Rust code.
+pub fn has_code_example(md: &str) -> bool {
+    code_block_langs(md).into_iter().any(|lang| LangString::parse(lang).rust)
+}
+
+/// Finds the tokens in the language strings of the fenced code blocks of `md`
+/// that look like misspellings of rustdoc's code block attributes, such as
+/// `should-panic`, `norun` or `rsut`. Such tokens silently turn the block into
+/// a non-Rust one. Returns each token along with the attribute it resembles.
+pub fn find_misspelled_attributes(md: &str) -> Vec<(String, &'static str)> {
+    let mut misspelled = Vec::new();
+    for lang in code_block_langs(md) {
+        let (_, _, lang) = split_cfgs(lang);
+        let tokens = lang.split(|c: char| !(c == '_' || c == '-' || c.is_alphanumeric()));
+        for token in tokens {
+            if token.is_empty() || CODE_BLOCK_ATTRIBUTES.contains(&token) {
+                continue
+            }
+            let normalized = token.to_ascii_lowercase().replace('-', "_");
+            let max_dist = cmp::max(token.len(), 3) / 3;
+            let attr = CODE_BLOCK_ATTRIBUTES.iter().find(|&&attr| {
+                attr == normalized || lev_distance(&normalized, attr) <= max_dist ||
+                    is_transposition(&normalized, attr)
+            });
+            if let Some(&attr) = attr {
+                misspelled.push((token.to_string(), attr));
+            }
+        }
+    }
+    misspelled
+}
+
+/// Whether `a` is `b` with two adjacent characters swapped, which the
+/// Levenshtein distance counts as two edits.
+fn is_transposition(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false
+    }
+    let diffs = (0..a.len()).filter(|&i| a[i] != b[i]).collect::<Vec<_>>();
+    diffs.len() == 2 && diffs[1] == diffs[0] + 1 &&
+        a[diffs[0]] == b[diffs[1]] && a[diffs[1]] == b[diffs[0]]
+}
+
+/// Whether `token` is an error code such as `E0308`.
+fn is_error_code(token: &str) -> bool {
+    token.starts_with("E") && token.len() == 5 && token[1..].parse::<u32>().is_ok()
+}
+
 pub fn plain_summary_line(md: &str) -> String {
     extern fn link(_ob: *mut hoedown_buffer,
                        _link: *const hoedown_buffer,
@@ -583,7 +808,7 @@ pub fn plain_summary_line(md: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::{LangString, Markdown};
-    use super::plain_summary_line;
+    use super::{find_misspelled_attributes, plain_summary_line};
     use html::render::reset_ids;
 
     #[test]
@@ -599,6 +824,8 @@ mod tests {
                 test_harness: test_harness,
                 compile_fail: compile_fail,
                 error_codes: error_codes,
+                cfgs: Vec::new(),
+                invalid_cfgs: Vec::new(),
             })
         }
 
@@ -618,6 +845,28 @@ mod tests {
         t("{.sh .should_panic}",   true,         false,  false,  true,  false, false, Vec::new());
         t("{.example .rust}",      false,        false,  false,  true,  false, false, Vec::new());
         t("{.test_harness .rust}", false,        false,  false,  true,  true,  false, Vec::new());
+        t("compile_fail,E0308",    false,        true,   false,  true,  false, true,
+                                   vec!("E0308".to_owned()));
+    }
+
+    #[test]
+    fn test_lang_string_parse_cfgs() {
+        fn t(s: &str, rust: bool, cfgs: &[&str], invalid_cfgs: &[&str]) {
+            let data = LangString::parse(s);
+            assert_eq!(data.rust, rust);
+            assert_eq!(data.cfgs, cfgs);
+            assert_eq!(data.invalid_cfgs, invalid_cfgs);
+        }
+
+        t("rust",                               true,  &[],                    &[]);
+        t("cfg(unix)",                          true,  &["unix"],              &[]);
+        t("rust,cfg(feature = \"foo\"),no_run", true,  &["feature=\"foo\""],   &[]);
+        t("cfg(a) cfg(b=\"c\")",                true,  &["a", "b=\"c\""],      &[]);
+        t("cfg(all(unix, foo))",                true,  &["unix", "foo"],       &[]);
+        t("cfg(all(a, all(b = \"c, d\")))",     true,  &["a", "b=\"c, d\""],   &[]);
+        t("cfg(any(a, b))",                     true,  &[],                    &["cfg(any(a, b))"]);
+        t("text,nocfg(a)",                      false, &[],                    &[]);
+        t("sh",                                 false, &[],                    &[]);
     }
 
     #[test]
@@ -688,4 +937,26 @@ mod tests {
         t("# top header", "top header");
         t("## header", "header");
     }
+
+    #[test]
+    fn test_find_misspelled_attributes() {
+        fn t(input: &str, expect: &[(&str, &str)]) {
+            let output = find_misspelled_attributes(input);
+            let output = output.iter().map(|&(ref t, a)| (&t[..], a)).collect::<Vec<_>>();
+            assert_eq!(output, expect);
+        }
+
+        t("```rust,no_run\n```", &[]);
+        t("```text\nfoo\n```", &[]);
+        t("```sh\n```\n```ignore\n```", &[]);
+        t("```haskell\n```\n```lua\n```\n```ts\n```", &[]);
+        t("```compile_fail,E0308\n```", &[]);
+        t("```should-panic\n```", &[("should-panic", "should_panic")]);
+        t("```should-panik\n```", &[("should-panik", "should_panic")]);
+        t("```rust,norun\n```", &[("norun", "no_run")]);
+        t("```rsut\n```", &[("rsut", "rust")]);
+        t("```\n```compile-fail\n```", &[]);
+        t("```rust,cfg(feature = \"tesst\")\n```", &[]);
+        t("~~~ignored\n~~~", &[("ignored", "ignore")]);
+    }
 }
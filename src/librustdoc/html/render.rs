@@ -71,8 +71,9 @@ use html::format::{TyParamBounds, WhereClause, href, AbiSpace};
 use html::format::{VisSpace, Method, UnsafetySpace, MutableSpace};
 use html::format::fmt_impl_for_trait_page;
 use html::item_type::ItemType;
-use html::markdown::{self, Markdown};
+use html::markdown::{self, Markdown, MarkdownWithLinks};
 use html::{highlight, layout};
+use span_map::{SpanMap, SourceSpan};
 
 /// A pair of name and its optional document.
 pub type NameDoc = (String, Option<String>);
@@ -123,6 +124,8 @@ pub struct SharedContext {
     /// The given user css file which allow to customize the generated
     /// documentation theme.
     pub css_file_extension: Option<PathBuf>,
+    /// The themes given with `--theme`, offered along with the built-in ones.
+    pub themes: Vec<PathBuf>,
 }
 
 /// Indicates where an external crate can be found.
@@ -249,6 +252,10 @@ pub struct Cache {
     /// Cache of where documentation for primitives can be found.
     pub primitive_locations: FnvHashMap<clean::PrimitiveType, CrateNum>,
 
+    /// The intra-doc links resolved in the documentation of each item, which
+    /// are turned into URLs when the item's documentation is rendered.
+    pub intra_doc_links: ::core::IntraDocLinks,
+
     // Note that external items for which `doc(hidden)` applies to are shown as
     // non-reachable while local items aren't. This is because we're reusing
     // the access levels from crateanalysis.
@@ -283,19 +290,33 @@ pub struct RenderInfo {
     pub external_typarams: FnvHashMap<DefId, String>,
     pub deref_trait_did: Option<DefId>,
     pub deref_mut_trait_did: Option<DefId>,
+    pub intra_doc_links: ::core::IntraDocLinks,
+    pub span_map: SpanMap,
 }
 
 /// Helper struct to render all source code to HTML pages
 struct SourceCollector<'a> {
     scx: &'a mut SharedContext,
 
+    /// Where the identifiers of the sources resolve to
+    span_map: &'a SpanMap,
+
     /// Root destination to place all HTML output into
     dst: PathBuf,
 }
 
 /// Wrapper struct to render the source code of a file. This will do things like
-/// adding line numbers to the left-hand side.
-struct Source<'a>(&'a str);
+/// adding line numbers to the left-hand side, linking identifiers to the URLs
+/// of their definitions and listing where the items defined in the file are
+/// used.
+struct Source<'a> {
+    contents: &'a str,
+    /// Byte ranges of `contents` to link and the URLs they link to
+    links: &'a [(usize, usize, String)],
+    /// Items defined in the file, with the URL of their definition and the
+    /// locations and URLs of their uses
+    used_by: &'a [(String, String, Vec<(String, String)>)],
+}
 
 // Helper structs for rendering items/sidebars and carrying along contextual
 // information
@@ -318,37 +339,24 @@ struct IndexItem {
     parent: Option<DefId>,
     parent_idx: Option<usize>,
     search_type: Option<IndexItemFunctionType>,
-}
-
-impl ToJson for IndexItem {
-    fn to_json(&self) -> Json {
-        assert_eq!(self.parent.is_some(), self.parent_idx.is_some());
-
-        let mut data = Vec::with_capacity(6);
-        data.push((self.ty as usize).to_json());
-        data.push(self.name.to_json());
-        data.push(self.path.to_json());
-        data.push(self.desc.to_json());
-        data.push(self.parent_idx.to_json());
-        data.push(self.search_type.to_json());
-
-        Json::Array(data)
-    }
+    /// The words of the item's documentation, for the full-text index.
+    terms: Vec<String>,
 }
 
 /// A type used for the search index.
 struct Type {
     name: Option<String>,
+    /// Names of the type's generic arguments, if any.
+    generics: Vec<String>,
 }
 
 impl ToJson for Type {
+    /// A type is written as its name, or as `[name, [generics...]]` if it has
+    /// generic arguments.
     fn to_json(&self) -> Json {
         match self.name {
-            Some(ref name) => {
-                let mut data = BTreeMap::new();
-                data.insert("name".to_owned(), name.to_json());
-                Json::Object(data)
-            },
+            Some(ref name) if self.generics.is_empty() => name.to_json(),
+            Some(ref name) => Json::Array(vec![name.to_json(), self.generics.to_json()]),
             None => Json::Null
         }
     }
@@ -361,15 +369,16 @@ struct IndexItemFunctionType {
 }
 
 impl ToJson for IndexItemFunctionType {
+    /// A function type is written as `[[inputs...], output]`, leaving out the
+    /// output of functions returning nothing.
     fn to_json(&self) -> Json {
-        // If we couldn't figure out a type, just write `null`.
+        // If we couldn't figure out a type, just write `0`.
         if self.inputs.iter().chain(self.output.iter()).any(|ref i| i.name.is_none()) {
-            Json::Null
+            Json::U64(0)
         } else {
-            let mut data = BTreeMap::new();
-            data.insert("inputs".to_owned(), self.inputs.to_json());
-            data.insert("output".to_owned(), self.output.to_json());
-            Json::Object(data)
+            let mut data = vec![self.inputs.to_json()];
+            data.extend(self.output.as_ref().map(|output| output.to_json()));
+            Json::Array(data)
         }
     }
 }
@@ -436,11 +445,19 @@ pub fn run(mut krate: clean::Crate,
            dst: PathBuf,
            passes: FnvHashSet<String>,
            css_file_extension: Option<PathBuf>,
+           themes: Vec<PathBuf>,
            renderinfo: RenderInfo) -> Result<(), Error> {
     let src_root = match krate.src.parent() {
         Some(p) => p.to_path_buf(),
         None => PathBuf::new(),
     };
+    let mut theme_names = vec!["main".to_string(), "dark".to_string()];
+    for theme in &themes {
+        let name = theme.file_stem().unwrap().to_string_lossy().into_owned();
+        if !theme_names.contains(&name) {
+            theme_names.push(name);
+        }
+    }
     let mut scx = SharedContext {
         src_root: src_root,
         passes: passes,
@@ -453,8 +470,10 @@ pub fn run(mut krate: clean::Crate,
             external_html: external_html.clone(),
             krate: krate.name.clone(),
             playground_url: "".to_string(),
+            themes: theme_names,
         },
         css_file_extension: css_file_extension.clone(),
+        themes: themes,
     };
 
     // Crawl the crate attributes looking for attributes which control how we're
@@ -493,14 +512,6 @@ pub fn run(mut krate: clean::Crate,
         }
     }
     try_err!(mkdir(&dst), &dst);
-    krate = render_sources(&dst, &mut scx, krate)?;
-    let cx = Context {
-        current: Vec::new(),
-        root_path: String::new(),
-        dst: dst,
-        render_redirect_pages: false,
-        shared: Arc::new(scx),
-    };
 
     // Crawl the crate to build various caches used for the output
     let RenderInfo {
@@ -509,6 +520,8 @@ pub fn run(mut krate: clean::Crate,
         external_typarams,
         deref_trait_did,
         deref_mut_trait_did,
+        intra_doc_links,
+        span_map,
     } = renderinfo;
 
     let external_paths = external_paths.into_iter()
@@ -526,6 +539,7 @@ pub fn run(mut krate: clean::Crate,
         parent_is_trait_impl: false,
         extern_locations: FnvHashMap(),
         primitive_locations: FnvHashMap(),
+        intra_doc_links: intra_doc_links,
         seen_modules: FnvHashSet(),
         seen_mod: false,
         stripped_mod: false,
@@ -540,7 +554,7 @@ pub fn run(mut krate: clean::Crate,
     // Cache where all our extern crates are located
     for &(n, ref e) in &krate.externs {
         cache.extern_locations.insert(n, (e.name.clone(),
-                                          extern_location(e, &cx.dst)));
+                                          extern_location(e, &dst)));
         let did = DefId { krate: n, index: CRATE_DEF_INDEX };
         cache.external_paths.insert(did, (vec![e.name.to_string()], ItemType::Module));
     }
@@ -562,7 +576,7 @@ pub fn run(mut krate: clean::Crate,
     krate = cache.fold_crate(krate);
 
     // Build our search index
-    let index = build_index(&krate, &mut cache);
+    let (index, docs_index) = build_index(&krate, &mut cache);
 
     // Freeze the cache now that the index has been built. Put an Arc into TLS
     // for future parallelization opportunities
@@ -570,17 +584,43 @@ pub fn run(mut krate: clean::Crate,
     CACHE_KEY.with(|v| *v.borrow_mut() = cache.clone());
     CURRENT_LOCATION_KEY.with(|s| s.borrow_mut().clear());
 
-    write_shared(&cx, &krate, &*cache, index)?;
+    // Sources are rendered once the cache is in place, as identifiers in them
+    // link to the documentation of items from other crates.
+    krate = render_sources(&dst, &mut scx, &span_map, krate)?;
+    CURRENT_LOCATION_KEY.with(|s| s.borrow_mut().clear());
+    let cx = Context {
+        current: Vec::new(),
+        root_path: String::new(),
+        dst: dst,
+        render_redirect_pages: false,
+        shared: Arc::new(scx),
+    };
+
+    write_shared(&cx, &krate, &*cache, index, docs_index)?;
 
     // And finally render the whole crate's documentation
     cx.krate(krate)
 }
 
-/// Build the search index from the collected metadata
-fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
+/// Build the search index from the collected metadata, along with the
+/// full-text index of the documentation of the indexed items.
+///
+/// To keep the index small for crates with many items, it is stored by
+/// column rather than by item: a string holding the type of each item as a
+/// single letter, then arrays of the names, descriptions, parents and
+/// function types, where `0` stands for no parent or function type. Paths
+/// are only listed for the items whose path differs from the previous one.
+fn build_index(krate: &clean::Crate, cache: &mut Cache) -> (String, String) {
     let mut nodeid_to_pathid = FnvHashMap();
-    let mut crate_items = Vec::with_capacity(cache.search_index.len());
+    let len = cache.search_index.len();
+    let mut crate_types = String::with_capacity(len);
+    let mut crate_names = Vec::with_capacity(len);
+    let mut crate_item_paths = Vec::<Json>::new();
+    let mut crate_descs = Vec::with_capacity(len);
+    let mut crate_parents = Vec::with_capacity(len);
+    let mut crate_fn_types = Vec::with_capacity(len);
     let mut crate_paths = Vec::<Json>::new();
+    let mut crate_terms = BTreeMap::<String, Vec<usize>>::new();
 
     let Cache { ref mut search_index,
                 ref orphan_impl_items,
@@ -598,6 +638,7 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
                 parent: Some(did),
                 parent_idx: None,
                 search_type: get_index_search_type(&item),
+                terms: index_terms(item.doc_value()),
             });
         }
     }
@@ -607,7 +648,7 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
     let mut lastpath = String::new();
     let mut lastpathid = 0usize;
 
-    for item in search_index {
+    for (i, item) in search_index.iter_mut().enumerate() {
         item.parent_idx = item.parent.map(|nodeid| {
             if nodeid_to_pathid.contains_key(&nodeid) {
                 *nodeid_to_pathid.get(&nodeid).unwrap()
@@ -622,34 +663,71 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
             }
         });
 
+        for term in item.terms.drain(..) {
+            crate_terms.entry(term).or_insert(Vec::new()).push(i);
+        }
+
         // Omit the parent path if it is same to that of the prior item.
-        if lastpath == item.path {
-            item.path.clear();
-        } else {
+        if lastpath != item.path {
+            crate_item_paths.push((i, item.path.clone()).to_json());
             lastpath = item.path.clone();
         }
-        crate_items.push(item.to_json());
+        crate_types.push((b'A' + item.ty as u8) as char);
+        crate_names.push(item.name.to_json());
+        crate_descs.push(item.desc.to_json());
+        crate_parents.push(item.parent_idx.map_or(0, |idx| idx + 1).to_json());
+        crate_fn_types.push(item.search_type.as_ref().map_or(Json::U64(0), |t| t.to_json()));
     }
 
     let crate_doc = krate.module.as_ref().map(|module| {
         Escape(&shorter(module.doc_value())).to_string()
     }).unwrap_or(String::new());
 
+    // The first two characters of the words of the full-text index, so that
+    // its file is only loaded for the crates that may match a search.
+    let mut term_prefixes = crate_terms.keys().map(|term| {
+        term.chars().take(2).collect::<String>()
+    }).collect::<Vec<_>>();
+    term_prefixes.dedup();
+
     let mut crate_data = BTreeMap::new();
     crate_data.insert("doc".to_owned(), Json::String(crate_doc));
-    crate_data.insert("items".to_owned(), Json::Array(crate_items));
-    crate_data.insert("paths".to_owned(), Json::Array(crate_paths));
-
-    // Collect the index into a string
-    format!("searchIndex[{}] = {};",
-            as_json(&krate.name),
-            Json::Object(crate_data))
+    crate_data.insert("t".to_owned(), Json::String(crate_types));
+    crate_data.insert("n".to_owned(), Json::Array(crate_names));
+    crate_data.insert("q".to_owned(), Json::Array(crate_item_paths));
+    crate_data.insert("d".to_owned(), Json::Array(crate_descs));
+    crate_data.insert("i".to_owned(), Json::Array(crate_parents));
+    crate_data.insert("f".to_owned(), Json::Array(crate_fn_types));
+    crate_data.insert("p".to_owned(), Json::Array(crate_paths));
+    crate_data.insert("w".to_owned(), Json::String(term_prefixes.join(" ")));
+
+    // The full-text index maps each word to the items whose documentation
+    // contains it. Item indices are increasing, so storing the differences
+    // between them keeps the numbers small.
+    let crate_terms = crate_terms.into_iter().map(|(term, items)| {
+        let mut last = 0;
+        let deltas = items.into_iter().map(|i| {
+            let delta = i - last;
+            last = i;
+            delta
+        }).collect::<Vec<_>>();
+        (term, deltas.to_json())
+    }).collect();
+
+    // Collect the indexes into strings
+    (format!("searchIndex[{}] = {};",
+             as_json(&krate.name),
+             Json::Object(crate_data)),
+     format!("addSearchDocs({}, {});",
+             as_json(&krate.name),
+             Json::Object(crate_terms)))
 }
 
 fn write_shared(cx: &Context,
                 krate: &clean::Crate,
                 cache: &Cache,
-                search_index: String) -> Result<(), Error> {
+                search_index: String,
+                search_docs: String) -> Result<(), Error> {
     // Write out the shared files. Note that these are shared among all rustdoc
     // docs placed in the output directory, so this needs to be a synchronized
     // operation with respect to all other rustdocs running around.
@@ -669,6 +747,16 @@ fn write_shared(cx: &Context,
           include_bytes!("static/rustdoc.css"))?;
     write(cx.dst.join("main.css"),
           include_bytes!("static/styles/main.css"))?;
+    write(cx.dst.join("dark.css"),
+          include_bytes!("static/styles/dark.css"))?;
+    write(cx.dst.join("storage.js"),
+          include_bytes!("static/storage.js"))?;
+    for theme in &cx.shared.themes {
+        let mut content = Vec::new();
+        let mut f = try_err!(File::open(theme), theme);
+        try_err!(f.read_to_end(&mut content), theme);
+        write(cx.dst.join(theme.file_name().unwrap()), &content)?;
+    }
     if let Some(ref css) = cx.shared.css_file_extension {
         let mut content = String::new();
         let css = css.as_path();
@@ -740,6 +828,13 @@ fn write_shared(cx: &Context,
     }
     try_err!(writeln!(&mut w, "initSearch(searchIndex);"), &dst);
 
+    // The full-text index is only loaded when searching, one crate at a time
+    let dst = cx.dst.join("search-doc");
+    try_err!(mkdir(&dst), &dst);
+    let dst = dst.join(&format!("{}.js", krate.name));
+    let mut w = try_err!(File::create(&dst), &dst);
+    try_err!(writeln!(&mut w, "{}", search_docs), &dst);
+
     // Update the list of all implementors for traits
     let dst = cx.dst.join("implementors");
     try_err!(mkdir(&dst), &dst);
@@ -802,7 +897,7 @@ fn write_shared(cx: &Context,
     Ok(())
 }
 
-fn render_sources(dst: &Path, scx: &mut SharedContext,
+fn render_sources(dst: &Path, scx: &mut SharedContext, span_map: &SpanMap,
                   krate: clean::Crate) -> Result<clean::Crate, Error> {
     info!("emitting source files");
     let dst = dst.join("src");
@@ -812,6 +907,7 @@ fn render_sources(dst: &Path, scx: &mut SharedContext,
     let mut folder = SourceCollector {
         dst: dst,
         scx: scx,
+        span_map: span_map,
     };
     Ok(folder.fold_crate(krate))
 }
@@ -946,12 +1042,14 @@ impl<'a> SourceCollector<'a> {
         let mut cur = self.dst.clone();
         let mut root_path = String::from("../../");
         let mut href = String::new();
+        let mut location = vec!["src".to_string(), self.scx.layout.krate.clone()];
         clean_srcpath(&self.scx.src_root, &p, false, |component| {
             cur.push(component);
             mkdir(&cur).unwrap();
             root_path.push_str("../");
             href.push_str(component);
             href.push('/');
+            location.push(component.to_string());
         });
         let mut fname = p.file_name().expect("source has no filename")
                          .to_os_string();
@@ -970,13 +1068,65 @@ impl<'a> SourceCollector<'a> {
             description: &desc,
             keywords: BASIC_KEYWORDS,
         };
+        let (links, used_by) = self.source_links(filename, &root_path, location);
+        let source = Source {
+            contents: contents,
+            links: &links,
+            used_by: &used_by,
+        };
         layout::render(&mut w, &self.scx.layout,
-                       &page, &(""), &Source(contents),
+                       &page, &(""), &source,
                        self.scx.css_file_extension.is_some())?;
         w.flush()?;
         self.scx.local_sources.insert(p, href);
         Ok(())
     }
+
+    /// Computes the links of the source file `filename`, rendered at
+    /// `location`: the URLs its identifiers link to, and the items it defines
+    /// along with where they are used.
+    fn source_links(&self, filename: &str, root_path: &str, location: Vec<String>)
+                    -> (Vec<(usize, usize, String)>,
+                        Vec<(String, String, Vec<(String, String)>)>) {
+        // `href` computes URLs relative to the current location.
+        CURRENT_LOCATION_KEY.with(|slot| *slot.borrow_mut() = location);
+
+        let links = match self.span_map.references.get(filename) {
+            Some(refs) => refs.iter().filter_map(|&(ref span, did)| {
+                self.definition_url(did, root_path).map(|url| (span.lo, span.hi, url))
+            }).collect(),
+            None => Vec::new(),
+        };
+        let mut used_by = Vec::new();
+        for (def, name, uses) in self.span_map.back_references(filename) {
+            let uses = uses.into_iter().map(|span| {
+                let path = Path::new(&span.filename);
+                let path = path.strip_prefix(&self.scx.src_root).unwrap_or(path);
+                (format!("{}:{}", path.display(), span.line), self.source_url(span, root_path))
+            }).collect();
+            used_by.push((name.to_string(), format!("#{}", def.line), uses));
+        }
+        (links, used_by)
+    }
+
+    /// The URL of the definition of `did`: its line in the rendered sources for
+    /// local items, its documentation for items of other crates.
+    fn definition_url(&self, did: DefId, root_path: &str) -> Option<String> {
+        match self.span_map.definitions.get(&did) {
+            Some(&(ref span, _)) => Some(self.source_url(span, root_path)),
+            None => href(did).map(|(url, _, _)| url),
+        }
+    }
+
+    /// The URL of the line `span` starts on in the rendered sources.
+    fn source_url(&self, span: &SourceSpan, root_path: &str) -> String {
+        let mut url = format!("{}src/{}", root_path, self.scx.layout.krate);
+        clean_srcpath(&self.scx.src_root, Path::new(&span.filename), true, |component| {
+            url.push('/');
+            url.push_str(component);
+        });
+        format!("{}.html#{}", url, span.line)
+    }
 }
 
 impl DocFolder for Cache {
@@ -1012,9 +1162,11 @@ impl DocFolder for Cache {
                 self.traits.insert(item.def_id, t.clone());
             }
 
-            // Collect all the implementors of traits.
+            // Collect all the implementors of traits. Impls synthesized for a
+            // particular type are only shown on that type's page.
             if let clean::ImplItem(ref i) = item.inner {
-                if let Some(did) = i.trait_.def_id() {
+                let is_synthesized = i.synthetic || i.blanket_impl.is_some();
+                if let (Some(did), false) = (i.trait_.def_id(), is_synthesized) {
                     self.implementors.entry(did).or_insert(vec![]).push(Implementor {
                         def_id: item.def_id,
                         stability: item.stability.clone(),
@@ -1082,6 +1234,7 @@ impl DocFolder for Cache {
                             parent: parent,
                             parent_idx: None,
                             search_type: get_index_search_type(&item),
+                            terms: index_terms(item.doc_value()),
                         });
                     }
                 }
@@ -1668,12 +1821,44 @@ fn shorter<'a>(s: Option<&'a str>) -> String {
     }
 }
 
+/// The distinct words of a documentation string, lowercased, as indexed for
+/// full-text search. Words shorter than three characters are left out.
+fn index_terms(s: Option<&str>) -> Vec<String> {
+    let mut terms = s.unwrap_or("")
+                     .split(|c: char| !c.is_alphanumeric() && c != '_')
+                     .filter(|word| word.chars().count() >= 3)
+                     .map(|word| word.to_lowercase())
+                     .collect::<Vec<_>>();
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
 #[inline]
 fn plain_summary_line(s: Option<&str>) -> String {
     let line = shorter(s).replace("\n", " ");
     markdown::plain_summary_line(&line[..])
 }
 
+/// Returns the labels and URLs of the intra-doc links of `item`'s documentation
+/// whose targets are documented.
+fn intra_doc_links(item: &clean::Item) -> Vec<(String, String)> {
+    let cache = cache();
+    let links = match cache.intra_doc_links.get(&item.def_id) {
+        Some(links) => links,
+        None => return Vec::new(),
+    };
+    links.iter().filter_map(|&(ref label, did, ref fragment)| {
+        href(did).map(|(mut url, _, _)| {
+            if let Some(ref fragment) = *fragment {
+                url.push('#');
+                url.push_str(fragment);
+            }
+            (label.clone(), url)
+        })
+    }).collect()
+}
+
 fn document(w: &mut fmt::Formatter, cx: &Context, item: &clean::Item) -> fmt::Result {
     document_stability(w, cx, item)?;
     document_full(w, item)?;
@@ -1688,14 +1873,15 @@ fn document_short(w: &mut fmt::Formatter, item: &clean::Item, link: AssocItemLin
         } else {
             format!("{}", &plain_summary_line(Some(s)))
         };
-        write!(w, "<div class='docblock'>{}</div>", Markdown(&markdown))?;
+        write!(w, "<div class='docblock'>{}</div>",
+               MarkdownWithLinks(&markdown, &intra_doc_links(item)))?;
     }
     Ok(())
 }
 
 fn document_full(w: &mut fmt::Formatter, item: &clean::Item) -> fmt::Result {
     if let Some(s) = item.doc_value() {
-        write!(w, "<div class='docblock'>{}</div>", Markdown(s))?;
+        write!(w, "<div class='docblock'>{}</div>", MarkdownWithLinks(s, &intra_doc_links(item)))?;
     }
     Ok(())
 }
@@ -1840,6 +2026,7 @@ fn item_module(w: &mut fmt::Formatter, cx: &Context,
                     String::new()
                 };
                 let doc_value = myitem.doc_value().unwrap_or("");
+                let docs = MarkdownWithLinks(doc_value, &intra_doc_links(myitem)).to_string();
                 write!(w, "
                        <tr class='{stab} module-item'>
                            <td><a class='{class}' href='{href}'
@@ -1850,7 +2037,7 @@ fn item_module(w: &mut fmt::Formatter, cx: &Context,
                        </tr>",
                        name = *myitem.name.as_ref().unwrap(),
                        stab_docs = stab_docs,
-                       docs = shorter(Some(&Markdown(doc_value).to_string())),
+                       docs = shorter(Some(&docs)),
                        class = item_type(myitem),
                        stab = myitem.stability_class(),
                        href = item_path(item_type(myitem), myitem.name.as_ref().unwrap()),
@@ -2678,14 +2865,38 @@ fn render_assoc_items(w: &mut fmt::Formatter,
             }).is_some();
             render_deref_methods(w, cx, impl_, containing_item, has_deref_mut)?;
         }
-        write!(w, "<h2 id='implementations'>Trait \
-                   Implementations</h2>")?;
-        for i in &traits {
-            let did = i.trait_did().unwrap();
-            let assoc_link = AssocItemLink::GotoSource(did, &i.inner_impl().provided_trait_methods);
-            render_impl(w, cx, i, assoc_link,
-                        RenderMode::Normal, containing_item.stable_since())?;
+        let (synthetic, traits): (Vec<_>, Vec<_>) = traits.into_iter().partition(|t| {
+            t.inner_impl().synthetic
+        });
+        let (blanket, traits): (Vec<_>, Vec<_>) = traits.into_iter().partition(|t| {
+            t.inner_impl().blanket_impl.is_some()
+        });
+        if !traits.is_empty() {
+            write!(w, "<h2 id='implementations'>Trait \
+                       Implementations</h2>")?;
+            render_impls(w, cx, &traits, containing_item)?;
+        }
+        if !synthetic.is_empty() {
+            write!(w, "<h2 id='synthetic-implementations'>Auto Trait \
+                       Implementations</h2>")?;
+            render_impls(w, cx, &synthetic, containing_item)?;
         }
+        if !blanket.is_empty() {
+            write!(w, "<h2 id='blanket-implementations'>Blanket \
+                       Implementations</h2>")?;
+            render_impls(w, cx, &blanket, containing_item)?;
+        }
+    }
+    Ok(())
+}
+
+fn render_impls(w: &mut fmt::Formatter, cx: &Context, traits: &[&Impl],
+                containing_item: &clean::Item) -> fmt::Result {
+    for i in traits {
+        let did = i.trait_did().unwrap();
+        let assoc_link = AssocItemLink::GotoSource(did, &i.inner_impl().provided_trait_methods);
+        render_impl(w, cx, i, assoc_link,
+                    RenderMode::Normal, containing_item.stable_since())?;
     }
     Ok(())
 }
@@ -2732,7 +2943,8 @@ fn render_impl(w: &mut fmt::Formatter, cx: &Context, i: &Impl, link: AssocItemLi
         write!(w, "</span>")?;
         write!(w, "</h3>\n")?;
         if let Some(ref dox) = i.impl_item.attrs.value("doc") {
-            write!(w, "<div class='docblock'>{}</div>", Markdown(dox))?;
+            write!(w, "<div class='docblock'>{}</div>",
+                   MarkdownWithLinks(dox, &intra_doc_links(&i.impl_item)))?;
         }
     }
 
@@ -2948,7 +3160,7 @@ impl<'a> fmt::Display for Sidebar<'a> {
 
 impl<'a> fmt::Display for Source<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        let Source(s) = *self;
+        let s = self.contents;
         let lines = s.lines().count();
         let mut cols = 0;
         let mut tmp = lines;
@@ -2961,7 +3173,21 @@ impl<'a> fmt::Display for Source<'a> {
             write!(fmt, "<span id=\"{0}\">{0:1$}</span>\n", i, cols)?;
         }
         write!(fmt, "</pre>")?;
-        write!(fmt, "{}", highlight::render_with_highlighting(s, None, None, None))?;
+        write!(fmt, "{}", highlight::render_with_links(s, self.links))?;
+        if !self.used_by.is_empty() {
+            write!(fmt, "<div class=\"used-by\"><h2>Used by</h2><ul>")?;
+            for &(ref name, ref url, ref uses) in self.used_by {
+                write!(fmt, "<li><a href=\"{}\"><code>{}</code></a>: ", url, Escape(name))?;
+                for (i, &(ref label, ref url)) in uses.iter().enumerate() {
+                    if i > 0 {
+                        write!(fmt, ", ")?;
+                    }
+                    write!(fmt, "<a href=\"{}\">{}</a>", url, Escape(label))?;
+                }
+                write!(fmt, "</li>")?;
+            }
+            write!(fmt, "</ul></div>")?;
+        }
         Ok(())
     }
 }
@@ -2997,7 +3223,13 @@ fn get_index_search_type(item: &clean::Item) -> Option<IndexItemFunctionType> {
         _ => return None
     };
 
-    let inputs = decl.inputs.values.iter().map(|arg| get_index_type(&arg.type_)).collect();
+    // `self` is indexed by name, searches match it against the parent type.
+    let inputs = decl.inputs.values.iter().map(|arg| match arg.to_self() {
+        Some(clean::SelfValue) | Some(clean::SelfBorrowed(..)) => {
+            Type { name: Some("self".to_string()), generics: Vec::new() }
+        }
+        _ => get_index_type(&arg.type_),
+    }).collect();
     let output = match decl.output {
         clean::FunctionRetTy::Return(ref return_type) => Some(get_index_type(return_type)),
         _ => None
@@ -3007,7 +3239,28 @@ fn get_index_search_type(item: &clean::Item) -> Option<IndexItemFunctionType> {
 }
 
 fn get_index_type(clean_type: &clean::Type) -> Type {
-    Type { name: get_index_type_name(clean_type).map(|s| s.to_ascii_lowercase()) }
+    Type {
+        name: get_index_type_name(clean_type).map(|s| s.to_ascii_lowercase()),
+        generics: get_index_type_generics(clean_type),
+    }
+}
+
+fn get_index_type_generics(clean_type: &clean::Type) -> Vec<String> {
+    match *clean_type {
+        clean::ResolvedPath { ref path, .. } => {
+            match path.segments[path.segments.len() - 1].params {
+                clean::PathParameters::AngleBracketed { ref types, .. } => {
+                    types.iter()
+                         .filter_map(get_index_type_name)
+                         .map(|s| s.to_ascii_lowercase())
+                         .collect()
+                }
+                _ => Vec::new(),
+            }
+        }
+        clean::BorrowedRef { ref type_, .. } => get_index_type_generics(type_),
+        _ => Vec::new(),
+    }
 }
 
 fn get_index_type_name(clean_type: &clean::Type) -> Option<String> {
@@ -9,7 +9,7 @@
 // except according to those terms.
 
 /*jslint browser: true, es5: true */
-/*globals $: true, rootPath: true */
+/*globals $: true, rootPath: true, currentTheme: true, switchTheme: true */
 
 (function() {
     "use strict";
@@ -147,6 +147,17 @@
             $("#help").addClass("hidden");
             $("body").removeClass("blur");
         }
+        if (!$(ev.target).closest(".theme-picker").length) {
+            $("#theme-choices").hide();
+        }
+    });
+
+    $("#theme-picker").on("click", function() {
+        $("#theme-choices").toggle();
+    });
+    $("#theme-choices > button").on("click", function() {
+        switchTheme(currentTheme, $(this).text());
+        $("#theme-choices").hide();
     });
 
     $('.version-selector').on('change', function() {
@@ -205,11 +216,28 @@
         };
     })();
 
+    function loadScript(url) {
+        var script = document.createElement('script');
+        script.src = url;
+        document.getElementsByTagName('head')[0].appendChild(script);
+    }
+
     function initSearch(rawSearchIndex) {
         var currentResults, index, searchIndex;
         var MAX_LEV_DISTANCE = 3;
         var params = getQueryStringParams();
 
+        // Index in `searchIndex` of the first item of each crate.
+        var crateOffsets = {};
+        // The first two characters of the words of each crate's full-text
+        // index, used to only load the indexes that may match a search.
+        var crateTermPrefixes = {};
+        // The full-text index of each crate, loaded on the first search that
+        // may match in the crate. It maps the words of the documentation to
+        // the items documented with them, as differences between the indices
+        // of successive items.
+        var searchDocs = {}, searchDocsRequested = {};
+
         // Populate search bar with query string search term when provided,
         // but only if the input bar is empty. This avoid the obnoxious issue
         // where you start trying to do a search, and the index loads, and
@@ -284,10 +312,8 @@
             } else if (val.search("->") > -1) {
                 var trimmer = function (s) { return s.trim(); };
                 var parts = val.split("->").map(trimmer);
-                var input = parts[0];
-                // sort inputs so that order does not matter
-                var inputs = input.split(",").map(trimmer).sort().toString();
-                var output = parts[1];
+                var inputs = parts[0] === "*" ? "*" : splitTypes(parts[0]).map(parseType);
+                var output = parts[1] === "*" ? "*" : parseType(parts[1]);
 
                 for (var i = 0; i < nSearchWords; ++i) {
                     var type = searchIndex[i].type;
@@ -295,15 +321,13 @@
                         continue;
                     }
 
-                    // sort index inputs so that order does not matter
-                    var typeInputs = type.inputs.map(function (input) {
-                        return input.name;
-                    }).sort();
-
+                    var parent = searchIndex[i].parent;
                     // allow searching for void (no output) functions as well
-                    var typeOutput = type.output ? type.output.name : "";
-                    if ((inputs === "*" || inputs === typeInputs.toString()) &&
-                        (output === "*" || output == typeOutput)) {
+                    var outputMatches = output === "*" ||
+                        (output.name === "" ? !type.output :
+                         type.output && typeMatches(output, type.output, parent));
+                    if ((inputs === "*" || inputsMatch(inputs, type.inputs, parent)) &&
+                        outputMatches && typePassesFilter(typeFilter, searchIndex[i].ty)) {
                         results.push({id: i, index: -1, dontValidate: true});
                     }
                 }
@@ -342,6 +366,31 @@
                         }
                     }
                 }
+
+                // then the items whose documentation contains all the words
+                var words = valLower.split(/\W+/).filter(function(word) {
+                    return word.length >= 3;
+                });
+                if (words.length > 0) {
+                    loadSearchDocs(words);
+                    var found = {};
+                    for (var i = 0; i < results.length; ++i) {
+                        found[results[i].id] = true;
+                    }
+                    var docIds = searchDocIds(words);
+                    for (var i = 0; i < docIds.length && results.length < max; ++i) {
+                        if (!found[docIds[i]] &&
+                            typePassesFilter(typeFilter, searchIndex[docIds[i]].ty)) {
+                            results.push({
+                                id: docIds[i],
+                                index: -1,
+                                // documentation matches go after name matches
+                                lev: MAX_LEV_DISTANCE + 1,
+                                dontValidate: true,
+                            });
+                        }
+                    }
+                }
             }
 
             var nresults = results.length;
@@ -448,6 +497,161 @@
             return results;
         }
 
+        /**
+         * Splits a comma-separated list of types, leaving alone the commas
+         * separating generic arguments.
+         * @param  {[string]} s [The list of types]
+         * @return {[string]}   [The types]
+         */
+        function splitTypes(s) {
+            var types = [], depth = 0, start = 0;
+            for (var i = 0; i < s.length; ++i) {
+                var c = s.charAt(i);
+                if (c === "<") {
+                    depth += 1;
+                } else if (c === ">") {
+                    depth -= 1;
+                } else if (c === "," && depth === 0) {
+                    types.push(s.substring(start, i).trim());
+                    start = i + 1;
+                }
+            }
+            types.push(s.substring(start).trim());
+            return types.filter(function(type) { return type !== ""; });
+        }
+
+        /**
+         * Parses a type of a type signature query, such as `vec<t>` or `&str`,
+         * keeping only the last segment of paths.
+         * @param  {[string]} s [The type]
+         * @return {[Object]}   [The name of the type and of its generics]
+         */
+        function parseType(s) {
+            var generics = [];
+            s = s.replace(/^&\s*(mut\s+)?/, "").trim();
+            var start = s.indexOf("<");
+            if (start > -1 && s.charAt(s.length - 1) === ">") {
+                generics = splitTypes(s.substring(start + 1, s.length - 1)).map(function(g) {
+                    return parseType(g).name;
+                });
+                s = s.substring(0, start);
+            }
+            var path = s.split("::");
+            return {name: path[path.length - 1].trim(), generics: generics};
+        }
+
+        /**
+         * Whether a type of the index matches a type of the query. Each of
+         * the generics of the query must be among those of the indexed type;
+         * `*` matches any type. `self` stands for the parent of the item.
+         */
+        function typeMatches(query, type, parent) {
+            if (type.name === "self" && parent !== undefined) {
+                return query.name === "*" || query.name === parent.name.toLowerCase();
+            }
+            if (query.name !== "*" && query.name !== type.name) {
+                return false;
+            }
+            var generics = type.generics || [];
+            for (var i = 0; i < query.generics.length; ++i) {
+                if (query.generics[i] !== "*" && generics.indexOf(query.generics[i]) === -1) {
+                    return false;
+                }
+            }
+            return true;
+        }
+
+        /**
+         * Whether the inputs of a function match those of the query, in any
+         * order.
+         */
+        function inputsMatch(queries, inputs, parent) {
+            if (queries.length !== inputs.length) {
+                return false;
+            }
+            var used = [];
+            for (var i = 0; i < queries.length; ++i) {
+                var found = false;
+                for (var j = 0; j < inputs.length && !found; ++j) {
+                    if (!used[j] && typeMatches(queries[i], inputs[j], parent)) {
+                        used[j] = found = true;
+                    }
+                }
+                if (!found) {
+                    return false;
+                }
+            }
+            return true;
+        }
+
+        /**
+         * Loads the full-text index of each crate that has words starting
+         * like each of `words`, unless it has already been requested.
+         * @param  {[string]} words [The lowercased words to look for]
+         */
+        function loadSearchDocs(words) {
+            for (var crate in crateTermPrefixes) {
+                if (!crateTermPrefixes.hasOwnProperty(crate) || searchDocsRequested[crate]) {
+                    continue;
+                }
+                var prefixes = crateTermPrefixes[crate];
+                var mayMatch = words.every(function(word) {
+                    return prefixes.hasOwnProperty(word.substring(0, 2));
+                });
+                if (mayMatch) {
+                    searchDocsRequested[crate] = true;
+                    loadScript(rootPath + 'search-doc/' + crate + '.js');
+                }
+            }
+        }
+
+        window.addSearchDocs = function(crate, terms) {
+            searchDocs[crate] = terms;
+            // run the current search again to include the new results
+            currentResults = null;
+            search();
+        };
+
+        /**
+         * Finds the items whose documentation contains all of `words`, as
+         * prefixes of its words.
+         * @param  {[string]} words [The lowercased words to look for]
+         * @return {[number]}       [The ids of the items in `searchIndex`]
+         */
+        function searchDocIds(words) {
+            var ids = [];
+            for (var crate in searchDocs) {
+                if (!searchDocs.hasOwnProperty(crate)) { continue; }
+                var terms = searchDocs[crate], matching = null;
+                for (var i = 0; i < words.length; ++i) {
+                    var found = {};
+                    for (var term in terms) {
+                        if (!terms.hasOwnProperty(term) || term.indexOf(words[i]) !== 0) {
+                            continue;
+                        }
+                        var deltas = terms[term], item = 0;
+                        for (var j = 0; j < deltas.length; ++j) {
+                            item += deltas[j];
+                            found[item] = true;
+                        }
+                    }
+                    if (matching === null) {
+                        matching = found;
+                    } else {
+                        for (var prev in matching) {
+                            if (!found[prev]) {
+                                delete matching[prev];
+                            }
+                        }
+                    }
+                }
+                for (var id in matching) {
+                    ids.push(crateOffsets[crate] + parseInt(id, 10));
+                }
+            }
+            return ids;
+        }
+
         /**
          * Validate performs the following boolean logic. For example:
          * "File::open" will give IF A PARENT EXISTS => ("file" && "open")
@@ -487,10 +691,13 @@
             var matches, type, query, raw = $('.search-input').val();
             query = raw;
 
-            matches = query.match(/^(fn|mod|struct|enum|trait|type|const|macro)\s*:\s*/i);
+            matches = query.match(/^(\w+)\s*:\s*/);
             if (matches) {
-                type = matches[1].replace(/^const$/, 'constant');
-                query = query.substring(matches[0].length);
+                var typename = matches[1].toLowerCase().replace(/^const$/, 'constant');
+                if (itemTypeFromName(typename) > -1) {
+                    type = typename;
+                    query = query.substring(matches[0].length);
+                }
             }
 
             return {
@@ -682,6 +889,26 @@
             return -1;
         }
 
+        /**
+         * Expands a type of the index, stored as its name or as
+         * `[name, [generics...]]`.
+         */
+        function indexType(type) {
+            if (typeof type === "string") {
+                return {name: type, generics: []};
+            }
+            return {name: type[0], generics: type[1]};
+        }
+
+        /**
+         * Expands a function type of the index, stored as
+         * `[[inputs...], output]` with the output left out if there is none.
+         */
+        function functionType(type) {
+            return {inputs: type[0].map(indexType),
+                    output: type.length > 1 ? indexType(type[1]) : null};
+        }
+
         function buildIndex(rawSearchIndex) {
             searchIndex = [];
             var searchWords = [];
@@ -697,17 +924,31 @@
                     desc: rawSearchIndex[crate].doc,
                     type: null,
                 });
-
-                // an array of [(Number) item type,
-                //              (String) name,
-                //              (String) full path or empty string for previous path,
-                //              (String) description,
-                //              (Number | null) the parent path index to `paths`]
-                //              (Object | null) the type of the function (if any)
-                var items = rawSearchIndex[crate].items;
+                crateOffsets[crate] = searchIndex.length;
+
+                var data = rawSearchIndex[crate];
+                // the item types, as one letter per item starting from `A`
+                var types = data.t;
+                // the names of the items
+                var names = data.n;
+                // an array of [(Number) item index, (String) full path] for
+                // the items whose path differs from that of the previous item
+                var itemPaths = data.q;
+                // the descriptions of the items
+                var descs = data.d;
+                // one more than the parent path index to `paths`, or 0
+                var parents = data.i;
+                // the types of the functions, or 0
+                var fnTypes = data.f;
                 // an array of [(Number) item type,
                 //              (String) name]
-                var paths = rawSearchIndex[crate].paths;
+                var paths = data.p;
+
+                crateTermPrefixes[crate] = {};
+                var prefixes = data.w.split(" ");
+                for (var i = 0; i < prefixes.length; ++i) {
+                    crateTermPrefixes[crate][prefixes[i]] = true;
+                }
 
                 // convert `paths` into an object form
                 var len = paths.length;
@@ -722,13 +963,17 @@
                 // operation that is cached for the life of the page state so that
                 // all other search operations have access to this cached data for
                 // faster analysis operations
-                var len = items.length;
-                var lastPath = "";
+                var len = names.length;
+                var lastPath = "", nextPath = 0;
                 for (var i = 0; i < len; ++i) {
-                    var rawRow = items[i];
-                    var row = {crate: crate, ty: rawRow[0], name: rawRow[1],
-                               path: rawRow[2] || lastPath, desc: rawRow[3],
-                               parent: paths[rawRow[4]], type: rawRow[5]};
+                    if (nextPath < itemPaths.length && itemPaths[nextPath][0] === i) {
+                        lastPath = itemPaths[nextPath][1];
+                        nextPath += 1;
+                    }
+                    var row = {crate: crate, ty: types.charCodeAt(i) - 65, name: names[i],
+                               path: lastPath, desc: descs[i],
+                               parent: parents[i] ? paths[parents[i] - 1] : undefined,
+                               type: fnTypes[i] ? functionType(fnTypes[i]) : null};
                     searchIndex.push(row);
                     if (typeof row.name === "string") {
                         var word = row.name.toLowerCase();
@@ -736,7 +981,6 @@
                     } else {
                         searchWords.push("");
                     }
-                    lastPath = row.path;
                 }
             }
             return searchWords;
@@ -836,8 +1080,8 @@
                 if (crates[i] === window.currentCrate) {
                     klass += ' current';
                 }
-                if (rawSearchIndex[crates[i]].items[0]) {
-                    var desc = rawSearchIndex[crates[i]].items[0][3];
+                if (rawSearchIndex[crates[i]].n.length > 0) {
+                    var desc = rawSearchIndex[crates[i]].d[0];
                     var link = $('<a>', {'href': '../' + crates[i] + '/index.html',
                                          'title': plainSummaryLine(desc),
                                          'class': klass}).text(crates[i]);
@@ -850,6 +1094,23 @@
 
     window.initSearch = initSearch;
 
+    // The search index is only loaded once needed: when the search bar gets
+    // used, when the page is opened on a search, and on the pages listing the
+    // documented crates in their sidebar.
+    var searchIndexRequested = false;
+    function loadSearchIndex() {
+        if (searchIndexRequested) {
+            return;
+        }
+        searchIndexRequested = true;
+        loadScript(rootPath + 'search-index.js');
+    }
+
+    $('.search-input').on('focus keydown', loadSearchIndex);
+    if (getQueryStringParams().search || rootPath === '../') {
+        loadSearchIndex();
+    }
+
     // delayed sidebar rendering.
     function initSidebarItems(items) {
         var sidebar = $('.sidebar');
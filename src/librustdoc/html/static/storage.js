@@ -0,0 +1,51 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*jslint browser: true, es5: true */
+
+// Applies the theme the reader picked last, before the page gets rendered to
+// avoid flashing the default theme. Loaded from the <head> of every page.
+
+var currentTheme = document.getElementById("themeStyle");
+
+function getCurrentValue(name) {
+    try {
+        return window.localStorage ? window.localStorage.getItem(name) : null;
+    } catch (e) {
+        // local storage may be disabled, in which case themes aren't saved
+        return null;
+    }
+}
+
+function updateLocalStorage(name, value) {
+    try {
+        if (window.localStorage) {
+            window.localStorage.setItem(name, value);
+        }
+    } catch (e) {
+        // see getCurrentValue
+    }
+}
+
+function switchTheme(styleElem, theme) {
+    // only switch to themes these docs were generated with
+    var themes = styleElem.getAttribute("data-themes").split(",");
+    if (themes.indexOf(theme) === -1) {
+        return;
+    }
+    var href = styleElem.getAttribute("href");
+    styleElem.setAttribute("href", href.substring(0, href.lastIndexOf("/") + 1) + theme + ".css");
+    updateLocalStorage("rustdoc-theme", theme);
+}
+
+var savedTheme = getCurrentValue("rustdoc-theme");
+if (savedTheme) {
+    switchTheme(currentTheme, savedTheme);
+}
@@ -0,0 +1,164 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Validation of the themes given with `--theme`. A theme replaces the default
+//! `main.css`, so it has to style at least every selector the latter does.
+
+use std::collections::BTreeSet;
+use std::io::prelude::*;
+use std::io;
+use std::path::Path;
+
+use externalfiles;
+
+/// The stylesheets rustdoc writes itself, which a theme can't replace.
+const BUILTIN_STYLESHEETS: &'static [&'static str] = &["rustdoc", "main", "dark"];
+
+/// Checks that the theme file at `path` can be used, reporting the problems
+/// on stderr.
+pub fn check(path: &str) -> bool {
+    let path = Path::new(path);
+    let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or(String::new());
+    if BUILTIN_STYLESHEETS.contains(&&name[..]) {
+        let _ = writeln!(&mut io::stderr(),
+                         "theme file `{}` would replace the built-in `{}.css`",
+                         path.display(), name);
+        return false;
+    }
+    let css = match externalfiles::load_string(path) {
+        Ok(Some(css)) => css,
+        Ok(None) => {
+            let _ = writeln!(&mut io::stderr(),
+                             "error reading `{}`: not UTF-8", path.display());
+            return false;
+        }
+        Err(e) => {
+            let _ = writeln!(&mut io::stderr(), "error reading `{}`: {}", path.display(), e);
+            return false;
+        }
+    };
+    let missing = missing_default_selectors(&css);
+    if !missing.is_empty() {
+        let _ = writeln!(&mut io::stderr(),
+                         "theme file `{}` is missing CSS rules from the default theme:",
+                         path.display());
+        for selector in &missing {
+            let _ = writeln!(&mut io::stderr(), "    {}", selector);
+        }
+        return false;
+    }
+    true
+}
+
+/// Returns the selectors of the rules of the stylesheet `css`, with their
+/// whitespace normalized. Grouped selectors are returned separately, and the
+/// ones nested in an at-rule such as `@media` are prefixed with it.
+pub fn selectors(css: &str) -> BTreeSet<String> {
+    let mut selectors = BTreeSet::new();
+    collect_selectors(&strip_comments(css), "", &mut selectors);
+    selectors
+}
+
+/// Returns the selectors styled by the default theme that `theme` doesn't
+/// style.
+pub fn missing_default_selectors(theme: &str) -> Vec<String> {
+    missing_selectors(include_str!("static/styles/main.css"), theme)
+}
+
+/// Returns the selectors styled by `base` that `theme` doesn't style.
+pub fn missing_selectors(base: &str, theme: &str) -> Vec<String> {
+    let theme = selectors(theme);
+    selectors(base).into_iter().filter(|s| !theme.contains(s)).collect()
+}
+
+fn strip_comments(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        rest = match rest[start + 2..].find("*/") {
+            Some(end) => &rest[start + 2 + end + 2..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+fn collect_selectors(css: &str, prefix: &str, selectors: &mut BTreeSet<String>) {
+    let mut rest = css;
+    while let Some(open) = rest.find('{') {
+        // Statements such as `@import ...;` may precede the selector.
+        let selector = rest[..open].rsplit(';').next().unwrap().trim();
+        let close = matching_brace(rest, open);
+        let body = &rest[open + 1..close];
+        if selector.starts_with("@media") || selector.starts_with("@supports") {
+            let prefix = format!("{}{} ", prefix, normalize(selector));
+            collect_selectors(body, &prefix, selectors);
+        } else if !selector.starts_with('@') {
+            for selector in selector.split(',') {
+                let selector = normalize(selector);
+                if !selector.is_empty() {
+                    selectors.insert(format!("{}{}", prefix, selector));
+                }
+            }
+        }
+        rest = if close < rest.len() { &rest[close + 1..] } else { "" };
+    }
+}
+
+/// Returns the position of the brace closing the one at `open`, or the length
+/// of `css` if it isn't closed.
+fn matching_brace(css: &str, open: usize) -> usize {
+    let mut depth = 0;
+    for (i, c) in css[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return open + i;
+                }
+            }
+            _ => {}
+        }
+    }
+    css.len()
+}
+
+fn normalize(selector: &str) -> String {
+    selector.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{selectors, missing_selectors, missing_default_selectors};
+
+    #[test]
+    fn test_selectors() {
+        let css = "/* a { } */ a, b  > c { color: red; }\n\
+                   @media (max-width: 700px) { .sidebar { display: none; } }\n\
+                   @font-face { font-family: x; }";
+        let found = selectors(css).into_iter().collect::<Vec<_>>();
+        assert_eq!(found, ["@media (max-width: 700px) .sidebar", "a", "b > c"]);
+    }
+
+    #[test]
+    fn test_missing_selectors() {
+        assert_eq!(missing_selectors("a {} b {}", "b { color: red }"), ["a"]);
+        assert!(missing_selectors("a {} b {}", "b, a { color: red }").is_empty());
+    }
+
+    #[test]
+    fn test_dark_theme_is_complete() {
+        let missing = missing_default_selectors(include_str!("static/styles/dark.css"));
+        assert!(missing.is_empty(), "missing selectors: {:?}", missing);
+    }
+}
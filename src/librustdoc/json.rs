@@ -0,0 +1,154 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Rustdoc's JSON backend.
+//!
+//! This serializes the cleaned crate, after all passes have run, into a single
+//! `<crate>.json` file for tools which want to consume documentation without
+//! scraping the HTML output.
+//!
+//! The item tree uses the `RustcEncodable` representation of the `clean`
+//! types. Next to it we emit the indices the HTML backend would otherwise
+//! compute in its `Cache`: the full path of every known item (including items
+//! of other crates) and the implementors of every trait. Definition ids appear
+//! as `{"krate": .., "index": ..}` objects inside items and as `"krate:index"`
+//! strings when used as keys of these indices.
+//!
+//! `FORMAT_VERSION` must be bumped whenever the shape of the output changes.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use rustc::hir::def_id::DefId;
+use serialize::json::as_json;
+
+use clean::{self, GetDefId};
+use fold::DocFolder;
+use html::item_type::ItemType;
+use html::render::{Error, RenderInfo};
+
+/// The version of the format emitted by this backend.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(RustcEncodable)]
+struct JsonCrate<'a> {
+    format_version: u32,
+    name: &'a str,
+    module: Option<&'a clean::Item>,
+    /// External crates, keyed by crate number.
+    externs: BTreeMap<String, &'a clean::ExternalCrate>,
+    primitives: &'a [clean::PrimitiveType],
+    /// Traits of other crates that are implemented or used in this one.
+    external_traits: BTreeMap<String, &'a clean::Trait>,
+    paths: BTreeMap<String, ItemPath>,
+    /// The impl items of each trait, keyed by the trait's id.
+    implementors: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(RustcEncodable)]
+struct ItemPath {
+    path: Vec<String>,
+    kind: &'static str,
+}
+
+/// Writes `krate` as `<crate name>.json` into `dst`.
+pub fn run(krate: &clean::Crate, dst: &Path, renderinfo: &RenderInfo) -> Result<(), Error> {
+    let mut paths = BTreeMap::new();
+    let mut implementors = BTreeMap::new();
+
+    for (&did, &(ref path, kind)) in &renderinfo.external_paths {
+        paths.insert(id(did), ItemPath {
+            path: path.clone(),
+            kind: ItemType::from(kind).css_class(),
+        });
+    }
+    // The impls synthesized for auto traits have no definition of their own
+    // and would share the id of their type, so they are left out.
+    let module = krate.module.clone().and_then(|m| AutoImplStripper.fold_item(m));
+    // The crate root is unnamed, so start with the crate's name.
+    if let Some(&clean::ModuleItem(ref m)) = module.as_ref().map(|m| &m.inner) {
+        let mut path = vec![krate.name.clone()];
+        for item in &m.items {
+            index_item(item, &mut path, &mut paths, &mut implementors);
+        }
+    }
+
+    let data = JsonCrate {
+        format_version: FORMAT_VERSION,
+        name: &krate.name,
+        module: module.as_ref(),
+        externs: krate.externs.iter().map(|&(cnum, ref ext)| (cnum.to_string(), ext)).collect(),
+        primitives: &krate.primitives,
+        external_traits: krate.external_traits.iter().map(|(&did, t)| (id(did), t)).collect(),
+        paths: paths,
+        implementors: implementors,
+    };
+
+    fs::create_dir_all(dst).map_err(|e| Error::new(e, dst))?;
+    let file = dst.join(&format!("{}.json", krate.name));
+    let mut w = BufWriter::new(File::create(&file).map_err(|e| Error::new(e, &file))?);
+    write!(w, "{}", as_json(&data)).map_err(|e| Error::new(e, &file))?;
+    Ok(())
+}
+
+/// Records the path of `item` and everything below it, as well as the trait
+/// implemented by any impl found along the way.
+fn index_item(item: &clean::Item,
+              path: &mut Vec<String>,
+              paths: &mut BTreeMap<String, ItemPath>,
+              implementors: &mut BTreeMap<String, Vec<String>>) {
+    if item.is_stripped() {
+        return;
+    }
+    match item.inner {
+        // Like in the HTML backend, impls synthesized for a particular type
+        // aren't listed among the implementors of their trait.
+        clean::ImplItem(ref i) if !i.synthetic && i.blanket_impl.is_none() => {
+            if let Some(did) = i.trait_.def_id() {
+                implementors.entry(id(did)).or_insert_with(Vec::new).push(id(item.def_id));
+            }
+        }
+        _ => {}
+    }
+
+    let name = match item.name {
+        Some(ref name) => name,
+        None => return,
+    };
+    path.push(name.clone());
+    paths.insert(id(item.def_id), ItemPath {
+        path: path.clone(),
+        kind: ItemType::from(item).css_class(),
+    });
+    if let clean::ModuleItem(ref m) = item.inner {
+        for child in &m.items {
+            index_item(child, path, paths, implementors);
+        }
+    }
+    path.pop();
+}
+
+struct AutoImplStripper;
+
+impl DocFolder for AutoImplStripper {
+    fn fold_item(&mut self, item: clean::Item) -> Option<clean::Item> {
+        match item.inner {
+            clean::ImplItem(ref i) if i.synthetic => return None,
+            _ => {}
+        }
+        self.fold_item_recur(item)
+    }
+}
+
+fn id(did: DefId) -> String {
+    format!("{}:{}", did.krate.as_u32(), did.index.as_u32())
+}
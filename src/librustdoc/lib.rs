@@ -60,7 +60,9 @@ use std::path::PathBuf;
 use std::process;
 use std::sync::mpsc::channel;
 
+use errors::emitter::ColorConfig;
 use externalfiles::ExternalHtml;
+use rustc::session::early_error;
 use rustc::session::search_paths::SearchPaths;
 use rustc::session::config::{ErrorOutputType, RustcOptGroup, nightly_options,
                              Externs};
@@ -69,6 +71,7 @@ use rustc::session::config::{ErrorOutputType, RustcOptGroup, nightly_options,
 pub mod externalfiles;
 
 pub mod clean;
+pub mod coverage;
 pub mod core;
 pub mod doctree;
 pub mod fold;
@@ -80,11 +83,14 @@ pub mod html {
     pub mod layout;
     pub mod markdown;
     pub mod render;
+    pub mod theme;
     pub mod toc;
 }
+pub mod json;
 pub mod markdown;
 pub mod passes;
 pub mod plugins;
+pub mod span_map;
 pub mod visit_ast;
 pub mod visit_lib;
 pub mod test;
@@ -118,7 +124,7 @@ pub fn opts() -> Vec<RustcOptGroup> {
         stable(optopt("r", "input-format", "the input type of the specified file",
                       "[rust]")),
         stable(optopt("w", "output-format", "the output type to write",
-                      "[html]")),
+                      "[html|json]")),
         stable(optopt("o", "output", "where to place the output", "PATH")),
         stable(optopt("", "crate-name", "specify the name of this crate", "NAME")),
         stable(optmulti("L", "library-path", "directory to add to crate search path",
@@ -159,6 +165,18 @@ pub fn opts() -> Vec<RustcOptGroup> {
         unstable(optopt("e", "extend-css",
                         "to redefine some css rules with a given file to generate doc with your \
                          own theme", "PATH")),
+        unstable(optmulti("", "theme",
+                          "additional themes which will be added to the generated docs, \
+                           selectable from the theme picker",
+                          "FILES")),
+        unstable(optflag("", "show-coverage",
+                         "print the documentation coverage of the crate instead of \
+                          generating documentation, as JSON with `--output-format json`")),
+        unstable(optflag("", "merge-doctests",
+                         "compile the doctests that allow it into a single test binary")),
+        unstable(optopt("", "error-format",
+                        "how errors and other messages are produced",
+                        "human|short|json|sarif")),
         unstable(optmulti("Z", "",
                           "internal and debugging options (only on nightly build)", "FLAG")),
         stable(optopt("", "sysroot", "Override the system root", "PATH")),
@@ -250,6 +268,14 @@ pub fn main_args(args: &[String]) -> isize {
         }
     }
 
+    let themes = matches.opt_strs("theme");
+    for theme in &themes {
+        if !html::theme::check(theme) {
+            return 1;
+        }
+    }
+    let themes = themes.into_iter().map(PathBuf::from).collect();
+
     let external_html = match ExternalHtml::load(
             &matches.opt_strs("html-in-header"),
             &matches.opt_strs("html-before-content"),
@@ -258,13 +284,15 @@ pub fn main_args(args: &[String]) -> isize {
         None => return 3
     };
     let crate_name = matches.opt_str("crate-name");
+    let merge_doctests = matches.opt_present("merge-doctests");
 
     match (should_test, markdown_input) {
         (true, true) => {
-            return markdown::test(input, cfgs, libs, externs, test_args)
+            return markdown::test(input, cfgs, libs, externs, test_args, merge_doctests)
         }
         (true, false) => {
-            return test::run(input, cfgs, libs, externs, test_args, crate_name)
+            return test::run(input, cfgs, libs, externs, test_args, crate_name,
+                             merge_doctests)
         }
         (false, true) => return markdown::render(input,
                                                  output.unwrap_or(PathBuf::from("doc")),
@@ -280,6 +308,10 @@ pub fn main_args(args: &[String]) -> isize {
         }
     };
     let Output { krate, passes, renderinfo } = out;
+    if matches.opt_present("show-coverage") {
+        coverage::run(&krate, matches.opt_str("w").as_ref().map(|s| &**s) == Some("json"));
+        return 0;
+    }
     info!("going to format");
     match matches.opt_str("w").as_ref().map(|s| &**s) {
         Some("html") | None => {
@@ -287,9 +319,14 @@ pub fn main_args(args: &[String]) -> isize {
                               output.unwrap_or(PathBuf::from("doc")),
                               passes.into_iter().collect(),
                               css_file_extension,
+                              themes,
                               renderinfo)
                 .expect("failed to generate documentation")
         }
+        Some("json") => {
+            json::run(&krate, &output.unwrap_or(PathBuf::from("doc")), &renderinfo)
+                .expect("failed to generate documentation")
+        }
         Some(s) => {
             println!("unknown output format: {}", s);
             return 1;
@@ -348,6 +385,20 @@ fn rust_input(cratefile: &str, externs: Externs, matches: &getopts::Matches) ->
     let cfgs = matches.opt_strs("cfg");
     let triple = matches.opt_str("target");
     let maybe_sysroot = matches.opt_str("sysroot").map(PathBuf::from);
+    let render_sources = !matches.opt_present("show-coverage") &&
+                         matches.opt_str("w").map_or(true, |w| w == "html");
+    let color = ColorConfig::Auto;
+    let error_format = match matches.opt_str("error-format").as_ref().map(|s| &s[..]) {
+        Some("human") | None => ErrorOutputType::HumanReadable(color),
+        Some("short") => ErrorOutputType::Short(color),
+        Some("json") => ErrorOutputType::Json,
+        Some("sarif") => ErrorOutputType::Sarif,
+        Some(arg) => {
+            early_error(ErrorOutputType::HumanReadable(color),
+                        &format!("argument for --error-format must be human, short, \
+                                  json or sarif (instead was `{}`)", arg))
+        }
+    };
 
     let cr = PathBuf::from(cratefile);
     info!("starting to run rustc");
@@ -357,7 +408,8 @@ fn rust_input(cratefile: &str, externs: Externs, matches: &getopts::Matches) ->
         use rustc::session::config::Input;
 
         tx.send(core::run_core(paths, cfgs, externs, Input::File(cr),
-                               triple, maybe_sysroot)).unwrap();
+                               triple, maybe_sysroot, error_format,
+                               render_sources)).unwrap();
     });
     let (mut krate, renderinfo) = rx.recv().unwrap();
     info!("finished with rustc");
@@ -365,6 +417,7 @@ fn rust_input(cratefile: &str, externs: Externs, matches: &getopts::Matches) ->
     if let Some(name) = matches.opt_str("crate-name") {
         krate.name = name
     }
+    krate.error_format = error_format;
 
     // Process all of the crate attributes, extracting plugin metadata along
     // with the passes which we are supposed to run.
@@ -21,19 +21,26 @@ pub fn collapse_docs(krate: clean::Crate) -> plugins::PluginResult {
     krate
 }
 
+/// Returns the documentation of `attrs` as the single doc attribute that the
+/// `collapse-docs` pass turns them into.
+pub fn collapsed_docs(attrs: &[clean::Attribute]) -> String {
+    let mut docstr = String::new();
+    for attr in attrs {
+        if let clean::NameValue(ref x, ref s) = *attr {
+            if "doc" == *x {
+                docstr.push_str(s);
+                docstr.push('\n');
+            }
+        }
+    }
+    docstr
+}
+
 struct Collapser;
 
 impl fold::DocFolder for Collapser {
     fn fold_item(&mut self, mut i: Item) -> Option<Item> {
-        let mut docstr = String::new();
-        for attr in &i.attrs {
-            if let clean::NameValue(ref x, ref s) = *attr {
-                if "doc" == *x {
-                    docstr.push_str(s);
-                    docstr.push('\n');
-                }
-            }
-        }
+        let docstr = collapsed_docs(&i.attrs);
         let mut a: Vec<clean::Attribute> = i.attrs.iter().filter(|&a| match a {
             &clean::NameValue(ref x, _) if "doc" == *x => false,
             _ => true
@@ -0,0 +1,230 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reports problems with the documentation as lints, honoring the levels set
+//! for them with `#[allow]`, `#[warn]`, `#[deny]` and `#[forbid]` attributes.
+
+use std::fs::File;
+use std::io::Read;
+use std::mem;
+use std::rc::Rc;
+
+use errors::{DiagnosticBuilder, Handler};
+use rustc::hir::def_id::DefId;
+use rustc::lint::{Level, Lint};
+use rustc::lint::builtin::{EMPTY_DOCS, MISSING_ITEM_DOCS, UNKNOWN_CODE_BLOCK_ATTRIBUTES};
+use rustc::lint::builtin::{UNRESOLVED_DOC_LINKS, WARNINGS};
+use rustc::util::nodemap::FnvHashMap;
+use syntax::codemap::CodeMap;
+use syntax_pos::{self, BytePos, DUMMY_SP};
+
+use clean::{self, Item};
+use core;
+use fold::DocFolder;
+use html::markdown;
+use plugins;
+
+pub fn lint_docs(mut krate: clean::Crate) -> plugins::PluginResult {
+    let codemap = Rc::new(CodeMap::new());
+    let handler = core::new_handler(krate.error_format, codemap.clone());
+    let krate = {
+        let mut linter = DocLinter {
+            handler: &handler,
+            codemap: codemap,
+            unresolved_links: mem::replace(&mut krate.unresolved_links, FnvHashMap()),
+            levels: Vec::new(),
+            parents: Vec::new(),
+        };
+        linter.fold_crate(krate)
+    };
+    handler.finish();
+    handler.abort_if_errors();
+    krate
+}
+
+struct DocLinter<'a> {
+    handler: &'a Handler,
+    codemap: Rc<CodeMap>,
+    unresolved_links: FnvHashMap<DefId, Vec<String>>,
+    /// The lint levels set by each of the items being visited, outermost first.
+    levels: Vec<Vec<(String, Level)>>,
+    /// Whether each of the items being visited is a documented module.
+    parents: Vec<bool>,
+}
+
+impl<'a> DocFolder for DocLinter<'a> {
+    fn fold_item(&mut self, item: Item) -> Option<Item> {
+        // Stripped and inlined items don't end up in this crate's docs, and
+        // neither do the children of stripped items.
+        if item.is_stripped() || !item.def_id.is_local() {
+            return Some(item)
+        }
+
+        self.levels.push(lint_levels(&item.attrs));
+        self.check_item(&item);
+        self.parents.push(item.is_mod() && item.doc_value().is_some());
+        let item = self.fold_item_recur(item);
+        self.parents.pop();
+        self.levels.pop();
+        item
+    }
+}
+
+impl<'a> DocLinter<'a> {
+    fn check_item(&self, item: &Item) {
+        let docs = item.attrs.iter().filter_map(|attr| match *attr {
+            clean::NameValue(ref name, ref doc) if name == "doc" => Some(&doc[..]),
+            _ => None,
+        }).collect::<Vec<_>>();
+
+        if docs.is_empty() {
+            if self.parents.last() == Some(&true) && needs_docs(item) {
+                let msg = format!("missing documentation for `{}`",
+                                  item.name.as_ref().map_or("", |s| &s[..]));
+                self.lint(MISSING_ITEM_DOCS, &item.source, &msg);
+            }
+            return
+        }
+
+        if docs.iter().all(|doc| doc.trim().is_empty()) {
+            self.lint(EMPTY_DOCS, &item.source, "empty doc comment");
+        }
+        for doc in &docs {
+            for (token, attr) in markdown::find_misspelled_attributes(doc) {
+                let msg = format!("unknown code block attribute `{}`", token);
+                let lint = UNKNOWN_CODE_BLOCK_ATTRIBUTES;
+                if let Some(mut diag) = self.struct_lint(lint, &item.source, &msg) {
+                    diag.help(&format!("did you mean `{}`?", attr));
+                    diag.emit();
+                }
+            }
+        }
+        if let Some(paths) = self.unresolved_links.get(&item.def_id) {
+            for path in paths {
+                let msg = format!("unresolved link to `{}`", path);
+                self.lint(UNRESOLVED_DOC_LINKS, &item.source, &msg);
+            }
+        }
+    }
+
+    fn lint(&self, lint: &'static Lint, span: &clean::Span, msg: &str) {
+        if let Some(mut diag) = self.struct_lint(lint, span, msg) {
+            diag.emit();
+        }
+    }
+
+    /// Builds the diagnostic for `lint` at the level it has for the current
+    /// item, or returns `None` if it is allowed.
+    fn struct_lint(&self, lint: &'static Lint, span: &clean::Span,
+                   msg: &str) -> Option<DiagnosticBuilder<'a>> {
+        let name = lint.name_lower();
+        let (mut level, mut note) = match self.level(&name) {
+            Some(level) => (level, None),
+            None => {
+                let level = lint.default_level;
+                (level, Some(format!("#[{}({})] on by default", level.as_str(), name)))
+            }
+        };
+        // Like in rustc, the level of the `warnings` lint applies to all the
+        // lints that are set to warn.
+        if level == Level::Warn {
+            if let Some(warnings) = self.level(&WARNINGS.name_lower()) {
+                level = warnings;
+                note = Some(format!("#[{}({})] implied by #[{}(warnings)]",
+                                    level.as_str(), name, level.as_str()));
+            }
+        }
+
+        let span = self.span(span);
+        let mut diag = match level {
+            Level::Allow => return None,
+            Level::Warn => self.handler.struct_span_warn(span, msg),
+            Level::Deny | Level::Forbid => self.handler.struct_span_err(span, msg),
+        };
+        if let Some(note) = note {
+            diag.note(&note);
+        }
+        Some(diag)
+    }
+
+    /// Returns the level set for the lint `name` by the items being visited,
+    /// if any. Levels set by inner items take precedence, unless the lint was
+    /// forbidden.
+    fn level(&self, name: &str) -> Option<Level> {
+        let mut result = None;
+        for &(ref lint, level) in self.levels.iter().flat_map(|levels| levels.iter()) {
+            if *lint == name && result != Some(Level::Forbid) {
+                result = Some(level);
+            }
+        }
+        result
+    }
+
+    /// Turns rustdoc's stringified span back into a span of the codemap,
+    /// loading the source file if needed.
+    fn span(&self, span: &clean::Span) -> syntax_pos::Span {
+        if span.filename.is_empty() {
+            return DUMMY_SP
+        }
+        let filemap = match self.codemap.get_filemap(&span.filename) {
+            Some(filemap) => filemap,
+            None => {
+                let mut src = String::new();
+                match File::open(&span.filename).and_then(|mut f| f.read_to_string(&mut src)) {
+                    Ok(_) => self.codemap.new_filemap_and_lines(&span.filename, None, &src),
+                    Err(_) => return DUMMY_SP,
+                }
+            }
+        };
+        let src = match filemap.src {
+            Some(ref src) => src,
+            None => return DUMMY_SP,
+        };
+        let lines = filemap.lines.borrow();
+        let pos = |line: usize, col: usize| {
+            lines.get(line.wrapping_sub(1)).map(|&start| {
+                let offset = (start - filemap.start_pos).0 as usize;
+                let col = src[offset..].char_indices().nth(col).map_or(0, |(i, _)| i);
+                start + BytePos(col as u32)
+            })
+        };
+        match (pos(span.loline, span.locol), pos(span.hiline, span.hicol)) {
+            (Some(lo), Some(hi)) => syntax_pos::mk_sp(lo, hi),
+            _ => DUMMY_SP,
+        }
+    }
+}
+
+/// Whether `item` should be documented when its module is.
+fn needs_docs(item: &Item) -> bool {
+    match item.inner {
+        clean::ImplItem(..) | clean::ImportItem(..) | clean::ExternCrateItem(..) |
+        clean::PrimitiveItem(..) => false,
+        _ => item.visibility == Some(clean::Public),
+    }
+}
+
+/// Returns the lint levels set by the `allow`, `warn`, `deny` and `forbid`
+/// attributes among `attrs`.
+fn lint_levels(attrs: &[clean::Attribute]) -> Vec<(String, Level)> {
+    let mut levels = Vec::new();
+    for attr in attrs {
+        if let clean::List(ref name, ref lints) = *attr {
+            if let Some(level) = Level::from_str(name) {
+                for lint in lints {
+                    if let clean::Word(ref lint) = *lint {
+                        levels.push((lint.clone(), level));
+                    }
+                }
+            }
+        }
+    }
+    levels
+}
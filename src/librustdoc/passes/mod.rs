@@ -19,7 +19,10 @@ use fold::FoldItem::Strip;
 use plugins;
 
 mod collapse_docs;
-pub use self::collapse_docs::collapse_docs;
+pub use self::collapse_docs::{collapse_docs, collapsed_docs};
+
+mod lint_docs;
+pub use self::lint_docs::lint_docs;
 
 mod strip_hidden;
 pub use self::strip_hidden::strip_hidden;
@@ -49,6 +52,8 @@ pub const PASSES: &'static [Pass] = &[
       implies strip-priv-imports"),
     ("strip-priv-imports", strip_priv_imports,
      "strips all private import statements (`use`, `extern crate`) from a crate"),
+    ("lint-docs", lint_docs,
+     "reports problems with the documentation, such as unresolved links, as lints"),
 ];
 
 pub const DEFAULT_PASSES: &'static [&'static str] = &[
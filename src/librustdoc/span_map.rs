@@ -0,0 +1,229 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Resolution data for the rendered source files: which spans of the local
+//! sources refer to which items, and where local items are defined. This is
+//! what turns identifiers in the `src/` pages into links to their definitions.
+
+use rustc::hir;
+use rustc::hir::def::Def;
+use rustc::hir::def_id::DefId;
+use rustc::hir::intravisit::{self, Visitor};
+use rustc::hir::map::DefPathData;
+use rustc::ty::{self, TyCtxt};
+use rustc::util::nodemap::FnvHashMap;
+use syntax::ast;
+use syntax_pos::{Span, Pos, NO_EXPANSION};
+
+/// A span of a local source file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub filename: String,
+    /// Byte offsets of the span within the file.
+    pub lo: usize,
+    pub hi: usize,
+    /// Line of the start of the span, starting from 1.
+    pub line: usize,
+}
+
+#[derive(Default)]
+pub struct SpanMap {
+    /// The references found in each source file, as the span of the name and
+    /// the item it refers to.
+    pub references: FnvHashMap<String, Vec<(SourceSpan, DefId)>>,
+    /// The span of the name of each local item where it is defined, along with
+    /// that name.
+    pub definitions: FnvHashMap<DefId, (SourceSpan, String)>,
+    /// The references to each item, ordered by file and position.
+    pub uses: FnvHashMap<DefId, Vec<SourceSpan>>,
+}
+
+impl SpanMap {
+    /// Returns the references to each local item defined in `filename`, in
+    /// order of definition.
+    pub fn back_references(&self, filename: &str)
+                           -> Vec<(&SourceSpan, &str, &[SourceSpan])> {
+        let mut defs = self.definitions.iter()
+            .filter(|&(_, &(ref span, _))| span.filename == filename)
+            .filter_map(|(did, &(ref span, ref name))| {
+                self.uses.get(did).map(|uses| (span, &name[..], &uses[..]))
+            })
+            .collect::<Vec<_>>();
+        defs.sort_by_key(|&(span, _, _)| span.lo);
+        defs
+    }
+}
+
+/// Walks the HIR of the local crate, recording the items its paths and method
+/// calls resolve to, and where its items are defined.
+pub fn collect<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> SpanMap {
+    let mut collector = SpanMapCollector { tcx: tcx, map: SpanMap::default() };
+    tcx.map.krate().visit_all_items(&mut collector);
+    let mut map = collector.map;
+    for refs in map.references.values_mut() {
+        refs.sort_by_key(|&(ref span, _)| span.lo);
+        refs.dedup();
+    }
+    for refs in map.references.values() {
+        for &(ref span, did) in refs {
+            map.uses.entry(did).or_insert(Vec::new()).push(span.clone());
+        }
+    }
+    for uses in map.uses.values_mut() {
+        uses.sort_by(|a, b| (&a.filename, a.lo).cmp(&(&b.filename, b.lo)));
+    }
+    map
+}
+
+struct SpanMapCollector<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    map: SpanMap,
+}
+
+impl<'a, 'tcx> SpanMapCollector<'a, 'tcx> {
+    /// Converts `span` into a span of a local file, unless it comes from a
+    /// macro expansion or has no file to point into.
+    fn source_span(&self, span: Span) -> Option<SourceSpan> {
+        if span.expn_id != NO_EXPANSION || span.lo > span.hi {
+            return None;
+        }
+        let codemap = self.tcx.sess.codemap();
+        let lo = codemap.lookup_byte_offset(span.lo);
+        let hi = codemap.lookup_byte_offset(span.hi);
+        if lo.fm.name != hi.fm.name || lo.fm.name.starts_with("<") {
+            return None;
+        }
+        Some(SourceSpan {
+            filename: lo.fm.name.clone(),
+            lo: lo.pos.to_usize(),
+            hi: hi.pos.to_usize(),
+            line: codemap.lookup_char_pos(span.lo).line,
+        })
+    }
+
+    /// Narrows `span` down to an occurrence of `name` in it as a whole word:
+    /// the first one, or the last one if `last` is set. The HIR doesn't keep
+    /// the spans of item names or of individual path segments.
+    fn name_span(&self, span: Span, name: &str, last: bool) -> Option<SourceSpan> {
+        let mut source_span = match self.source_span(span) {
+            Some(span) => span,
+            None => return None,
+        };
+        let snippet = match self.tcx.sess.codemap().span_to_snippet(span) {
+            Ok(snippet) => snippet,
+            Err(_) => return None,
+        };
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+        let mut found = None;
+        for (i, _) in snippet.match_indices(name) {
+            let before = snippet[..i].chars().next_back();
+            let after = snippet[i + name.len()..].chars().next();
+            if before.map_or(false, &is_ident) || after.map_or(false, &is_ident) {
+                continue;
+            }
+            found = Some(i);
+            if !last {
+                break;
+            }
+        }
+        let offset = match found {
+            Some(offset) => offset,
+            None => return None,
+        };
+        source_span.line += snippet[..offset].matches('\n').count();
+        source_span.lo += offset;
+        source_span.hi = source_span.lo + name.len();
+        Some(source_span)
+    }
+
+    fn add_definition(&mut self, id: ast::NodeId, span: Span, name: ast::Name) {
+        let name = name.as_str();
+        if let Some(span) = self.name_span(span, &name, false) {
+            let did = self.tcx.map.local_def_id(id);
+            self.map.definitions.insert(did, (span, name.to_string()));
+        }
+    }
+
+    fn add_reference(&mut self, span: SourceSpan, did: DefId) {
+        self.map.references.entry(span.filename.clone()).or_insert(Vec::new()).push((span, did));
+    }
+
+    /// The item a path resolved to, if it is one that can be linked to.
+    fn def_target(&self, def: Def) -> Option<DefId> {
+        match def {
+            Def::Local(..) | Def::Upvar(..) | Def::Label(..) | Def::PrimTy(..) |
+            Def::SelfTy(..) | Def::TyParam(..) | Def::Err => None,
+            // Tuple and unit struct expressions resolve to the constructor.
+            Def::Struct(did) => match self.tcx.def_key(did).disambiguated_data.data {
+                DefPathData::StructCtor => self.tcx.parent_def_id(did),
+                _ => Some(did),
+            },
+            def => Some(def.def_id()),
+        }
+    }
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for SpanMapCollector<'a, 'tcx> {
+    fn visit_item(&mut self, item: &'tcx hir::Item) {
+        match item.node {
+            hir::ItemUse(..) | hir::ItemImpl(..) | hir::ItemDefaultImpl(..) => {}
+            _ => self.add_definition(item.id, item.span, item.name),
+        }
+        intravisit::walk_item(self, item)
+    }
+
+    fn visit_trait_item(&mut self, item: &'tcx hir::TraitItem) {
+        self.add_definition(item.id, item.span, item.name);
+        intravisit::walk_trait_item(self, item)
+    }
+
+    fn visit_impl_item(&mut self, item: &'tcx hir::ImplItem) {
+        self.add_definition(item.id, item.span, item.name);
+        intravisit::walk_impl_item(self, item)
+    }
+
+    fn visit_foreign_item(&mut self, item: &'tcx hir::ForeignItem) {
+        self.add_definition(item.id, item.span, item.name);
+        intravisit::walk_foreign_item(self, item)
+    }
+
+    fn visit_struct_field(&mut self, field: &'tcx hir::StructField) {
+        self.add_definition(field.id, field.span, field.name);
+        intravisit::walk_struct_field(self, field)
+    }
+
+    fn visit_variant(&mut self, v: &'tcx hir::Variant, g: &'tcx hir::Generics,
+                     item_id: ast::NodeId) {
+        self.add_definition(v.node.data.id(), v.span, v.node.name);
+        intravisit::walk_variant(self, v, g, item_id)
+    }
+
+    fn visit_path(&mut self, path: &'tcx hir::Path, id: ast::NodeId) {
+        let target = self.tcx.expect_def_or_none(id).and_then(|def| self.def_target(def));
+        if let (Some(did), Some(segment)) = (target, path.segments.last()) {
+            if let Some(span) = self.name_span(path.span, &segment.name.as_str(), true) {
+                self.add_reference(span, did);
+            }
+        }
+        intravisit::walk_path(self, path)
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx hir::Expr) {
+        if let hir::ExprMethodCall(ref name, _, _) = expr.node {
+            let method_call = ty::MethodCall::expr(expr.id);
+            let target = self.tcx.tables.borrow().method_map.get(&method_call)
+                                                 .map(|method| method.def_id);
+            if let (Some(did), Some(span)) = (target, self.source_span(name.span)) {
+                self.add_reference(span, did);
+            }
+        }
+        intravisit::walk_expr(self, expr)
+    }
+}
@@ -13,12 +13,13 @@ use std::env;
 use std::ffi::OsString;
 use std::io::prelude::*;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::panic::{self, AssertUnwindSafe};
 use std::process::Command;
 use std::rc::Rc;
 use std::str;
 use std::sync::{Arc, Mutex};
+use std::thread;
 
 use testing;
 use rustc_lint;
@@ -57,7 +58,8 @@ pub fn run(input: &str,
            libs: SearchPaths,
            externs: Externs,
            mut test_args: Vec<String>,
-           crate_name: Option<String>)
+           crate_name: Option<String>,
+           merge_doctests: bool)
            -> isize {
     let input_path = PathBuf::from(input);
     let input = config::Input::File(input_path.clone());
@@ -108,6 +110,7 @@ pub fn run(input: &str,
         input: input,
         populated_all_crate_impls: Cell::new(false),
         external_traits: Default::default(),
+        blanket_impls: Default::default(),
         deref_trait_did: Cell::new(None),
         deref_mut_trait_did: Cell::new(None),
         access_levels: Default::default(),
@@ -130,6 +133,7 @@ pub fn run(input: &str,
                                        libs,
                                        externs,
                                        false,
+                                       merge_doctests,
                                        opts);
     collector.fold_crate(krate);
 
@@ -179,27 +183,6 @@ fn runtest(test: &str, cratename: &str, cfgs: Vec<String>, libs: SearchPaths,
     // the test harness wants its own `main` & top level functions, so
     // never wrap the test in `fn main() { ... }`
     let test = maketest(test, Some(cratename), as_test_harness, opts);
-    let input = config::Input::Str {
-        name: driver::anon_src(),
-        input: test.to_owned(),
-    };
-    let outputs = OutputTypes::new(&[(OutputType::Exe, None)]);
-
-    let sessopts = config::Options {
-        maybe_sysroot: Some(env::current_exe().unwrap().parent().unwrap()
-                                              .parent().unwrap().to_path_buf()),
-        search_paths: libs,
-        crate_types: vec!(config::CrateTypeExecutable),
-        output_types: outputs,
-        externs: externs,
-        cg: config::CodegenOptions {
-            prefer_dynamic: true,
-            .. config::basic_codegen_options()
-        },
-        test: as_test_harness,
-        unstable_features: UnstableFeatures::from_environment(),
-        ..config::basic_options().clone()
-    };
 
     // Shuffle around a few input and output handles here. We're going to pass
     // an explicit handle into rustc to collect output messages, but we also
@@ -211,13 +194,6 @@ fn runtest(test: &str, cratename: &str, cfgs: Vec<String>, libs: SearchPaths,
     //
     // The basic idea is to not use a default Handler for rustc, and then also
     // not print things by default to the actual stderr.
-    struct Sink(Arc<Mutex<Vec<u8>>>);
-    impl Write for Sink {
-        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
-            Write::write(&mut *self.0.lock().unwrap(), data)
-        }
-        fn flush(&mut self) -> io::Result<()> { Ok(()) }
-    }
     struct Bomb(Arc<Mutex<Vec<u8>>>, Box<Write+Send>);
     impl Drop for Bomb {
         fn drop(&mut self) {
@@ -225,53 +201,25 @@ fn runtest(test: &str, cratename: &str, cfgs: Vec<String>, libs: SearchPaths,
         }
     }
     let data = Arc::new(Mutex::new(Vec::new()));
-    let codemap = Rc::new(CodeMap::new());
-    let emitter = errors::emitter::EmitterWriter::new(box Sink(data.clone()),
-                                                      Some(codemap.clone()));
     let old = io::set_panic(box Sink(data.clone()));
     let _bomb = Bomb(data.clone(), old.unwrap_or(box io::stdout()));
 
-    // Compile the code
-    let diagnostic_handler = errors::Handler::with_emitter(true, false, box emitter);
-
-    let dep_graph = DepGraph::new(false);
-    let cstore = Rc::new(CStore::new(&dep_graph));
-    let sess = session::build_session_(sessopts,
-                                       &dep_graph,
-                                       None,
-                                       diagnostic_handler,
-                                       codemap,
-                                       cstore.clone());
-    rustc_lint::register_builtins(&mut sess.lint_store.borrow_mut(), Some(&sess));
-
-    let outdir = Mutex::new(TempDir::new("rustdoctest").ok().expect("rustdoc needs a tempdir"));
-    let libdir = sess.target_filesearch(PathKind::All).get_lib_path();
-    let mut control = driver::CompileController::basic();
-    let cfg = config::build_configuration(&sess, config::parse_cfgspecs(cfgs.clone()));
-    let out = Some(outdir.lock().unwrap().path().to_path_buf());
-
-    if no_run {
-        control.after_analysis.stop = Compilation::Stop;
-    }
-
-    let res = panic::catch_unwind(AssertUnwindSafe(|| {
-        driver::compile_input(&sess, &cstore, cfg.clone(),
-                              &input, &out,
-                              &None, None, &control)
-    }));
+    let outdir = TempDir::new("rustdoctest").ok().expect("rustdoc needs a tempdir");
+    let (res, libdir) = compile(&test, cfgs, libs, externs, as_test_harness, no_run,
+                                outdir.path(), data.clone());
 
     match res {
         Ok(r) => {
             match r {
                 Err(count) => {
                     if count > 0 && compile_fail == false {
-                        sess.fatal("aborting due to previous error(s)")
+                        panic!("aborting due to previous error(s)")
                     } else if count == 0 && compile_fail == true {
                         panic!("test compiled while it wasn't supposed to")
                     }
                     if count > 0 && error_codes.len() > 0 {
                         let out = String::from_utf8(data.lock().unwrap().to_vec()).unwrap();
-                        error_codes.retain(|err| !out.contains(err));
+                        error_codes.retain(|err| !out.contains(&format!("error[{}]", err)));
                     }
                 }
                 Ok(()) if compile_fail => panic!("test compiled while it wasn't supposed to"),
@@ -284,7 +232,7 @@ fn runtest(test: &str, cratename: &str, cfgs: Vec<String>, libs: SearchPaths,
             }
             if error_codes.len() > 0 {
                 let out = String::from_utf8(data.lock().unwrap().to_vec()).unwrap();
-                error_codes.retain(|err| !out.contains(err));
+                error_codes.retain(|err| !out.contains(&format!("error[{}]", err)));
             }
         }
     }
@@ -295,18 +243,94 @@ fn runtest(test: &str, cratename: &str, cfgs: Vec<String>, libs: SearchPaths,
 
     if no_run { return }
 
-    // Run the code!
-    //
+    run_test_binary(Command::new(&outdir.path().join("rust_out")), libdir, should_panic);
+}
+
+struct Sink(Arc<Mutex<Vec<u8>>>);
+impl Write for Sink {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        Write::write(&mut *self.0.lock().unwrap(), data)
+    }
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+/// Compiles `src` into an executable named `rust_out` in `outdir`, writing the
+/// compiler's messages to `output`.
+///
+/// Returns the result of the compilation, `Err` if the compiler panicked,
+/// along with the target's library directory the executable must be run with.
+fn compile(src: &str, cfgs: Vec<String>, libs: SearchPaths, externs: Externs,
+           as_test_harness: bool, no_run: bool, outdir: &Path,
+           output: Arc<Mutex<Vec<u8>>>)
+           -> (thread::Result<Result<(), usize>>, PathBuf) {
+    let input = config::Input::Str {
+        name: driver::anon_src(),
+        input: src.to_owned(),
+    };
+    let outputs = OutputTypes::new(&[(OutputType::Exe, None)]);
+
+    let sessopts = config::Options {
+        maybe_sysroot: Some(env::current_exe().unwrap().parent().unwrap()
+                                              .parent().unwrap().to_path_buf()),
+        search_paths: libs,
+        crate_types: vec!(config::CrateTypeExecutable),
+        output_types: outputs,
+        externs: externs,
+        cg: config::CodegenOptions {
+            prefer_dynamic: true,
+            .. config::basic_codegen_options()
+        },
+        test: as_test_harness,
+        unstable_features: UnstableFeatures::from_environment(),
+        ..config::basic_options().clone()
+    };
+
+    let codemap = Rc::new(CodeMap::new());
+    let emitter = errors::emitter::EmitterWriter::new(box Sink(output),
+                                                      Some(codemap.clone()));
+
+    // Compile the code
+    let diagnostic_handler = errors::Handler::with_emitter(true, false, box emitter);
+
+    let dep_graph = DepGraph::new(false);
+    let cstore = Rc::new(CStore::new(&dep_graph));
+    let sess = session::build_session_(sessopts,
+                                       &dep_graph,
+                                       None,
+                                       diagnostic_handler,
+                                       codemap,
+                                       cstore.clone());
+    rustc_lint::register_builtins(&mut sess.lint_store.borrow_mut(), Some(&sess));
+
+    let libdir = sess.target_filesearch(PathKind::All).get_lib_path();
+    let mut control = driver::CompileController::basic();
+    let cfg = config::build_configuration(&sess, config::parse_cfgspecs(cfgs));
+    let out = Some(outdir.to_path_buf());
+
+    if no_run {
+        control.after_analysis.stop = Compilation::Stop;
+    }
+
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        driver::compile_input(&sess, &cstore, cfg,
+                              &input, &out,
+                              &None, None, &control)
+    }));
+    (res, libdir)
+}
+
+/// Runs a compiled test, failing if its exit status doesn't match
+/// `should_panic`.
+fn run_test_binary(mut cmd: Command, libdir: PathBuf, should_panic: bool) {
     // We're careful to prepend the *target* dylib search path to the child's
     // environment to ensure that the target loads the right libraries at
     // runtime. It would be a sad day if the *host* libraries were loaded as a
     // mistake.
-    let mut cmd = Command::new(&outdir.lock().unwrap().path().join("rust_out"));
     let var = DynamicLibrary::envvar();
     let newpath = {
         let path = env::var_os(var).unwrap_or(OsString::new());
         let mut path = env::split_paths(&path).collect::<Vec<_>>();
-        path.insert(0, libdir.clone());
+        path.insert(0, libdir);
         env::join_paths(path).unwrap()
     };
     cmd.env(var, &newpath);
@@ -328,6 +352,183 @@ fn runtest(test: &str, cratename: &str, cfgs: Vec<String>, libs: SearchPaths,
     }
 }
 
+/// A doctest that can be compiled along with others into a single binary.
+#[derive(Clone)]
+struct MergedTest {
+    test: String,
+    no_run: bool,
+    /// Whether the test broke the build of the merged binary, and is compiled
+    /// on its own instead.
+    left_out: bool,
+}
+
+/// Doctests sharing one compilation when `--merge-doctests` is passed.
+///
+/// Each test becomes a function of a single program whose `main` calls the
+/// one named by its first argument, so the program is built once and then
+/// run in a separate process per test. Should the merged program fail to
+/// build, the tests its errors point to are left out of it and compiled on
+/// their own, so the failures are reported against the tests that caused
+/// them. Errors that can't be traced back to a test make every test fall
+/// back to being compiled on its own.
+struct MergedTests {
+    tests: Mutex<Vec<MergedTest>>,
+    /// The merged binary and its library directory, built by the first test
+    /// to run. `Some(None)` if building it failed.
+    binary: Mutex<Option<Option<(TempDir, PathBuf)>>>,
+    cratename: String,
+    cfgs: Vec<String>,
+    libs: SearchPaths,
+    externs: Externs,
+    opts: TestOptions,
+}
+
+impl MergedTests {
+    /// Whether a doctest can be part of the merged binary: it must be an
+    /// ordinary example that isn't ignored and needs no crate attributes,
+    /// configuration, `main` function or extern crates of its own.
+    fn accepts(test: &str, should_panic: bool, should_ignore: bool, as_test_harness: bool,
+               compile_fail: bool, cfgs: &[String]) -> bool {
+        let (crate_attrs, _) = partition_source(test);
+        !should_panic && !should_ignore && !as_test_harness && !compile_fail &&
+            cfgs.is_empty() &&
+            crate_attrs.trim().is_empty() &&
+            !test.contains("fn main") && !test.contains("extern crate")
+    }
+
+    fn add(&self, test: String, no_run: bool) -> usize {
+        let mut tests = self.tests.lock().unwrap();
+        tests.push(MergedTest { test: test, no_run: no_run, left_out: false });
+        tests.len() - 1
+    }
+
+    fn run(&self, index: usize) {
+        let built = {
+            let mut binary = self.binary.lock().unwrap();
+            if binary.is_none() {
+                // A panic while building would poison the lock and fail every
+                // merged test, so fall back to compiling the tests separately.
+                let built = panic::catch_unwind(AssertUnwindSafe(|| self.build()));
+                *binary = Some(built.unwrap_or(None));
+            }
+            binary.as_ref().unwrap().as_ref().map(|&(ref outdir, ref libdir)| {
+                (outdir.path().join("rust_out"), libdir.clone())
+            })
+        };
+        let test = self.tests.lock().unwrap()[index].clone();
+        match built {
+            Some((path, libdir)) if !test.left_out => {
+                if !test.no_run {
+                    let mut cmd = Command::new(&path);
+                    cmd.arg(index.to_string());
+                    run_test_binary(cmd, libdir, false);
+                }
+            }
+            _ => {
+                runtest(&test.test, &self.cratename, self.cfgs.clone(), self.libs.clone(),
+                        self.externs.clone(), false, test.no_run, false, false, Vec::new(),
+                        &self.opts);
+            }
+        }
+    }
+
+    /// Builds the merged program, leaving out the tests its errors point to
+    /// until it builds. Returns `None` if no test is left or an error can't
+    /// be traced back to a test.
+    fn build(&self) -> Option<(TempDir, PathBuf)> {
+        loop {
+            let (prog, lines) = self.program();
+            if lines.is_empty() {
+                return None
+            }
+            info!("merged test program: {}", prog);
+
+            // The compiler's messages are only used to find the tests that
+            // broke the build: they are reported again when those tests are
+            // compiled separately.
+            let output = Arc::new(Mutex::new(Vec::new()));
+            let old = io::set_panic(box Sink(output.clone()));
+            let outdir = TempDir::new("rustdoctest").ok().expect("rustdoc needs a tempdir");
+            let (res, libdir) = compile(&prog, self.cfgs.clone(), self.libs.clone(),
+                                        self.externs.clone(), false, false, outdir.path(),
+                                        output.clone());
+            io::set_panic(old.unwrap_or(box io::stderr()));
+            match res {
+                Ok(Ok(())) => return Some((outdir, libdir)),
+                Ok(Err(_)) => {}
+                Err(_) => return None,
+            }
+
+            let output = String::from_utf8_lossy(&output.lock().unwrap()).into_owned();
+            let failing = error_lines(&output).into_iter().map(|line| {
+                lines.iter().find(|&&(_, start, end)| start <= line && line <= end)
+                     .map(|&(index, _, _)| index)
+            }).collect::<Option<Vec<_>>>();
+            let failing = match failing {
+                Some(ref failing) if !failing.is_empty() => failing,
+                _ => return None,
+            };
+            let mut tests = self.tests.lock().unwrap();
+            for &index in failing {
+                debug!("leaving merged test {} out of the merged program", index);
+                tests[index].left_out = true;
+            }
+        }
+    }
+
+    /// Returns the merged program along with the index and the first and last
+    /// lines of each test in it.
+    fn program(&self) -> (String, Vec<(usize, usize, usize)>) {
+        let tests = self.tests.lock().unwrap();
+        let mut prog = String::new();
+        let mut lines = Vec::new();
+
+        for attr in &self.opts.attrs {
+            prog.push_str(&format!("#![{}]\n", attr));
+        }
+        if !self.opts.no_crate_inject && self.cratename != "std" &&
+           tests.iter().any(|t| !t.left_out && t.test.contains(&self.cratename[..])) {
+            prog.push_str(&format!("extern crate {};\n", self.cratename));
+        }
+        let mut line = prog.matches('\n').count() + 1;
+        for (i, test) in tests.iter().enumerate() {
+            if test.left_out {
+                continue
+            }
+            let function = format!("fn __doctest_{}() {{\n{}\n}}\n", i, test.test.trim());
+            let len = function.matches('\n').count();
+            lines.push((i, line, line + len - 1));
+            line += len;
+            prog.push_str(&function);
+        }
+
+        prog.push_str("fn main() {\n    match &::std::env::args().nth(1).unwrap()[..] {\n");
+        for &(i, _, _) in &lines {
+            prog.push_str(&format!("        \"{0}\" => __doctest_{0}(),\n", i));
+        }
+        prog.push_str("        _ => unreachable!(),\n    }\n}\n");
+        (prog, lines)
+    }
+}
+
+/// Returns the lines of the merged program the errors in the compiler's
+/// `output` point to.
+fn error_lines(output: &str) -> Vec<usize> {
+    let location = format!("--> {}:", driver::anon_src());
+    let mut lines = Vec::new();
+    let mut in_error = false;
+    for line in output.lines() {
+        if line.starts_with("error") {
+            in_error = true;
+        } else if in_error && line.trim_left().starts_with(&location[..]) {
+            in_error = false;
+            let line = line.trim_left()[location.len()..].split(':').next().unwrap();
+            lines.extend(line.parse::<usize>().ok());
+        }
+    }
+    lines
+}
+
 pub fn maketest(s: &str, cratename: Option<&str>, dont_insert_main: bool,
                 opts: &TestOptions) -> String {
     let (crate_attrs, everything_else) = partition_source(s);
@@ -401,11 +602,25 @@ pub struct Collector {
     current_header: Option<String>,
     cratename: String,
     opts: TestOptions,
+    merged: Option<Arc<MergedTests>>,
 }
 
 impl Collector {
     pub fn new(cratename: String, cfgs: Vec<String>, libs: SearchPaths, externs: Externs,
-               use_headers: bool, opts: TestOptions) -> Collector {
+               use_headers: bool, merge_doctests: bool, opts: TestOptions) -> Collector {
+        let merged = if merge_doctests {
+            Some(Arc::new(MergedTests {
+                tests: Mutex::new(Vec::new()),
+                binary: Mutex::new(None),
+                cratename: cratename.clone(),
+                cfgs: cfgs.clone(),
+                libs: libs.clone(),
+                externs: externs.clone(),
+                opts: opts.clone(),
+            }))
+        } else {
+            None
+        };
         Collector {
             tests: Vec::new(),
             names: Vec::new(),
@@ -417,12 +632,58 @@ impl Collector {
             current_header: None,
             cratename: cratename,
             opts: opts,
+            merged: merged,
         }
     }
 
     pub fn add_test(&mut self, test: String,
                     should_panic: bool, no_run: bool, should_ignore: bool,
-                    as_test_harness: bool, compile_fail: bool, error_codes: Vec<String>) {
+                    as_test_harness: bool, compile_fail: bool, error_codes: Vec<String>,
+                    cfgs: Vec<String>) {
+        let name = self.next_test_name();
+        let merged = match self.merged {
+            Some(ref merged) if MergedTests::accepts(&test, should_panic, should_ignore,
+                                                     as_test_harness, compile_fail,
+                                                     &cfgs) => {
+                Some(merged.clone())
+            }
+            _ => None,
+        };
+        let merged = match merged {
+            Some(merged) => merged,
+            None => return self.add_standalone_test(name, test, should_panic, no_run,
+                                                    should_ignore, as_test_harness,
+                                                    compile_fail, error_codes, cfgs),
+        };
+        debug!("Creating merged test {}: {}", name, test);
+        let index = merged.add(test, no_run);
+        self.tests.push(testing::TestDescAndFn {
+            desc: testing::TestDesc {
+                name: testing::DynTestName(name),
+                ignore: should_ignore,
+                should_panic: testing::ShouldPanic::No,
+                timeout: None,
+            },
+            testfn: testing::DynTestFn(box move|| merged.run(index)),
+        });
+    }
+
+    /// Adds a test for a code block that can't be tested as written, which
+    /// fails with `msg` explaining why.
+    pub fn add_failing_test(&mut self, msg: String, should_ignore: bool) {
+        let name = self.next_test_name();
+        self.tests.push(testing::TestDescAndFn {
+            desc: testing::TestDesc {
+                name: testing::DynTestName(name),
+                ignore: should_ignore,
+                should_panic: testing::ShouldPanic::No,
+                timeout: None,
+            },
+            testfn: testing::DynTestFn(box move|| panic!("{}", msg)),
+        });
+    }
+
+    fn next_test_name(&mut self) -> String {
         let name = if self.use_headers {
             let s = self.current_header.as_ref().map(|s| &**s).unwrap_or("");
             format!("{}_{}", s, self.cnt)
@@ -430,7 +691,15 @@ impl Collector {
             format!("{}_{}", self.names.join("::"), self.cnt)
         };
         self.cnt += 1;
-        let cfgs = self.cfgs.clone();
+        name
+    }
+
+    fn add_standalone_test(&mut self, name: String, test: String,
+                           should_panic: bool, no_run: bool, should_ignore: bool,
+                           as_test_harness: bool, compile_fail: bool,
+                           error_codes: Vec<String>, cfgs: Vec<String>) {
+        let mut test_cfgs = self.cfgs.clone();
+        test_cfgs.extend(cfgs);
         let libs = self.libs.clone();
         let externs = self.externs.clone();
         let cratename = self.cratename.to_string();
@@ -442,11 +711,12 @@ impl Collector {
                 ignore: should_ignore,
                 // compiler failures are test failures
                 should_panic: testing::ShouldPanic::No,
+                timeout: None,
             },
             testfn: testing::DynTestFn(box move|| {
                 runtest(&test,
                         &cratename,
-                        cfgs,
+                        test_cfgs,
                         libs,
                         externs,
                         should_panic,
@@ -303,6 +303,9 @@ declare_features! (
     // Used to identify the `compiler_builtins` crate
     // rustc internal
     (active, compiler_builtins, "1.13.0", None),
+
+    // Allows `#[timeout = "<seconds>"]` on `#[test]` functions
+    (active, test_timeout, "1.13.0", None),
 );
 
 declare_features! (
@@ -421,6 +424,9 @@ pub const KNOWN_ATTRIBUTES: &'static [(&'static str, AttributeType, AttributeGat
     ("derive", Normal, Ungated),
     ("should_panic", Normal, Ungated),
     ("ignore", Normal, Ungated),
+    ("timeout", Normal, Gated("test_timeout",
+                              "the `#[timeout]` attribute is an experimental feature",
+                              cfg_fn!(test_timeout))),
     ("no_implicit_prelude", Normal, Ungated),
     ("reexport_test_harness_main", Normal, Ungated),
     ("link_args", Normal, Ungated),
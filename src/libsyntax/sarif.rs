@@ -0,0 +1,289 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A SARIF 2.1 emitter for errors.
+//!
+//! Unlike the JSON emitter, which writes one object per diagnostic, a SARIF
+//! log is a single document. Diagnostics are therefore collected as they are
+//! emitted and the whole log is written out when the session finishes, or
+//! when the emitter is dropped while unwinding from a fatal error.
+//!
+//! Each diagnostic becomes a SARIF `result`: primary spans are its
+//! `locations`, secondary spans and spanned sub-diagnostics its
+//! `relatedLocations`, error codes its `ruleId` and suggestions its `fixes`.
+//! Sub-diagnostics without a span are appended to the result's message.
+
+use codemap::CodeMap;
+use syntax_pos::{Span, MultiSpan};
+use errors::registry::Registry;
+use errors::{DiagnosticBuilder, SubDiagnostic, RenderSpan, CodeSuggestion, CodeMapper, Level};
+use errors::emitter::Emitter;
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::io::{self, Write};
+
+use rustc_serialize::json::Json;
+
+const SARIF_SCHEMA: &'static str =
+    "https://docs.oasis-open.org/sarif/sarif/v2.1.0/os/schemas/sarif-schema-2.1.0.json";
+
+pub struct SarifEmitter {
+    dst: Box<Write + Send>,
+    registry: Option<Registry>,
+    cm: Rc<CodeMapper + 'static>,
+    /// Rules referenced by the results so far, keyed by error code.
+    rules: BTreeMap<String, Json>,
+    results: Vec<Json>,
+    /// Whether to write a log even if nothing was emitted. The short-lived
+    /// emitters used for early errors leave this off so that they don't
+    /// produce an extra, empty log next to the session's one.
+    write_empty: bool,
+    /// Whether the log has been written already.
+    finished: bool,
+}
+
+impl SarifEmitter {
+    pub fn basic() -> SarifEmitter {
+        SarifEmitter::new(None, Rc::new(CodeMap::new()), false)
+    }
+
+    pub fn stderr(registry: Option<Registry>,
+                  code_map: Rc<CodeMap>) -> SarifEmitter {
+        SarifEmitter::new(registry, code_map, true)
+    }
+
+    fn new(registry: Option<Registry>,
+           code_map: Rc<CodeMap>,
+           write_empty: bool) -> SarifEmitter {
+        SarifEmitter {
+            dst: Box::new(io::stderr()),
+            registry: registry,
+            cm: code_map,
+            rules: BTreeMap::new(),
+            results: vec![],
+            write_empty: write_empty,
+            finished: false,
+        }
+    }
+
+    /// Builds the complete SARIF log for everything emitted so far.
+    fn log(&self) -> Json {
+        let mut driver = vec![
+            ("name", string("rustc")),
+            ("informationUri", string("https://www.rust-lang.org/")),
+        ];
+        if !self.rules.is_empty() {
+            driver.push(("rules", Json::Array(self.rules.values().cloned().collect())));
+        }
+        let run = object(vec![
+            ("tool", object(vec![("driver", object(driver))])),
+            ("columnKind", string("unicodeCodePoints")),
+            ("results", Json::Array(self.results.clone())),
+        ]);
+        object(vec![
+            ("$schema", string(SARIF_SCHEMA)),
+            ("version", string("2.1.0")),
+            ("runs", Json::Array(vec![run])),
+        ])
+    }
+
+    fn result(&mut self, db: &DiagnosticBuilder, level: &'static str) -> Json {
+        let mut message = db.message.clone();
+        let mut locations = vec![];
+        let mut related = vec![];
+        let mut fixes = vec![];
+
+        for span_label in db.span.span_labels() {
+            let location = self.location(span_label.span, span_label.label.as_ref());
+            if span_label.is_primary {
+                locations.push(location);
+            } else {
+                related.push(location);
+            }
+        }
+
+        for child in &db.children {
+            match child.render_span {
+                Some(RenderSpan::Suggestion(ref suggestion)) => {
+                    fixes.push(self.fix(&child.message, suggestion));
+                }
+                Some(RenderSpan::FullSpan(ref msp)) => {
+                    self.push_child(&mut message, &mut related, child, msp);
+                }
+                None => {
+                    self.push_child(&mut message, &mut related, child, &child.span);
+                }
+            }
+        }
+
+        let mut fields = vec![];
+        if let Some(ref code) = db.code {
+            fields.push(("ruleId", string(code)));
+            self.add_rule(code);
+        }
+        fields.push(("level", string(level)));
+        fields.push(("message", text(&message)));
+        if !locations.is_empty() {
+            fields.push(("locations", Json::Array(locations)));
+        }
+        if !related.is_empty() {
+            fields.push(("relatedLocations", Json::Array(related)));
+        }
+        if !fixes.is_empty() {
+            fields.push(("fixes", Json::Array(fixes)));
+        }
+        object(fields)
+    }
+
+    /// Records a sub-diagnostic either as related locations (if it has a
+    /// span) or as an extra line of the result message.
+    fn push_child(&self,
+                  message: &mut String,
+                  related: &mut Vec<Json>,
+                  child: &SubDiagnostic,
+                  msp: &MultiSpan) {
+        let child_msg = format!("{}: {}", child.level.to_str(), child.message);
+        let span_labels = msp.span_labels();
+        if span_labels.is_empty() {
+            message.push('\n');
+            message.push_str(&child_msg);
+            return;
+        }
+        for span_label in span_labels {
+            let label = match span_label.label {
+                Some(label) => format!("{} ({})", child_msg, label),
+                None => child_msg.clone(),
+            };
+            related.push(self.location(span_label.span, Some(&label)));
+        }
+    }
+
+    fn add_rule(&mut self, code: &str) {
+        if self.rules.contains_key(code) {
+            return;
+        }
+        let mut fields = vec![("id", string(code))];
+        let explanation = self.registry
+                              .as_ref()
+                              .and_then(|registry| registry.find_description(code));
+        if let Some(explanation) = explanation {
+            fields.push(("fullDescription", text(explanation)));
+        }
+        self.rules.insert(code.to_owned(), object(fields));
+    }
+
+    fn location(&self, span: Span, label: Option<&String>) -> Json {
+        let mut fields = vec![("physicalLocation", object(vec![
+            ("artifactLocation", self.artifact(span)),
+            ("region", self.region(span)),
+        ]))];
+        if let Some(label) = label {
+            fields.push(("message", text(label)));
+        }
+        object(fields)
+    }
+
+    fn artifact(&self, span: Span) -> Json {
+        let name = self.cm.span_to_filename(span);
+        object(vec![("uri", string(&name.replace("\\", "/")))])
+    }
+
+    fn region(&self, span: Span) -> Json {
+        let start = self.cm.lookup_char_pos(span.lo);
+        let end = self.cm.lookup_char_pos(span.hi);
+        let offset = span.lo.0 - start.file.start_pos.0;
+        object(vec![
+            ("startLine", Json::U64(start.line as u64)),
+            ("startColumn", Json::U64(start.col.0 as u64 + 1)),
+            ("endLine", Json::U64(end.line as u64)),
+            ("endColumn", Json::U64(end.col.0 as u64 + 1)),
+            ("byteOffset", Json::U64(offset as u64)),
+            ("byteLength", Json::U64((span.hi.0 - span.lo.0) as u64)),
+        ])
+    }
+
+    fn fix(&self, description: &str, suggestion: &CodeSuggestion) -> Json {
+        // Replacements have to be grouped by the file they apply to.
+        let mut changes: BTreeMap<String, Vec<Json>> = BTreeMap::new();
+        let spans = suggestion.msp.primary_spans();
+        for (&span, substitute) in spans.iter().zip(&suggestion.substitutes) {
+            let replacement = object(vec![
+                ("deletedRegion", self.region(span)),
+                ("insertedContent", text(substitute)),
+            ]);
+            changes.entry(self.cm.span_to_filename(span))
+                   .or_insert_with(Vec::new)
+                   .push(replacement);
+        }
+        let changes = changes.into_iter().map(|(file, replacements)| {
+            object(vec![
+                ("artifactLocation", object(vec![("uri", string(&file.replace("\\", "/")))])),
+                ("replacements", Json::Array(replacements)),
+            ])
+        }).collect();
+        object(vec![
+            ("description", text(description)),
+            ("artifactChanges", Json::Array(changes)),
+        ])
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit(&mut self, db: &DiagnosticBuilder) {
+        let level = match db.level {
+            Level::Bug | Level::Fatal | Level::PhaseFatal | Level::Error => "error",
+            Level::Warning => "warning",
+            Level::Note | Level::Help => "note",
+            Level::Cancelled => return,
+        };
+        // The message the driver stops on after errors doesn't describe a
+        // problem of its own, and would be a result without a location.
+        if db.level == Level::Fatal && db.message.starts_with("aborting due to ") {
+            return;
+        }
+        let result = self.result(db, level);
+        self.results.push(result);
+    }
+
+    fn finish(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        if self.results.is_empty() && !self.write_empty {
+            return;
+        }
+        let log = self.log();
+        // This also runs while unwinding from a fatal error, so don't panic.
+        if let Err(e) = writeln!(&mut self.dst, "{}", log.pretty()) {
+            let _ = writeln!(io::stderr(), "failed to print diagnostics: {:?}", e);
+        }
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+fn object(fields: Vec<(&str, Json)>) -> Json {
+    Json::Object(fields.into_iter().map(|(k, v)| (k.to_owned(), v)).collect())
+}
+
+fn string(s: &str) -> Json {
+    Json::String(s.to_owned())
+}
+
+/// A SARIF `message` (or `artifactContent`) object.
+fn text(s: &str) -> Json {
+    object(vec![("text", string(s))])
+}
@@ -51,7 +51,8 @@ struct Test {
     path: Vec<ast::Ident> ,
     bench: bool,
     ignore: bool,
-    should_panic: ShouldPanic
+    should_panic: ShouldPanic,
+    timeout: Option<u64>
 }
 
 struct TestCtxt<'a> {
@@ -132,7 +133,8 @@ impl<'a> fold::Folder for TestHarnessGenerator<'a> {
                         path: self.cx.path.clone(),
                         bench: is_bench_fn(&self.cx, &i),
                         ignore: is_ignored(&i),
-                        should_panic: should_panic(&i)
+                        should_panic: should_panic(&i),
+                        timeout: test_timeout(&self.cx, &i)
                     };
                     self.cx.testfns.push(test);
                     self.tests.push(i.ident);
@@ -415,6 +417,23 @@ fn should_panic(i: &ast::Item) -> ShouldPanic {
     }
 }
 
+fn test_timeout(cx: &TestCtxt, i: &ast::Item) -> Option<u64> {
+    let attr = match i.attrs.iter().find(|attr| attr.check_name("timeout")) {
+        Some(attr) => attr,
+        None => return None,
+    };
+    match attr.value_str().and_then(|s| s.parse::<u64>().ok()) {
+        Some(secs) if secs > 0 => Some(secs),
+        _ => {
+            cx.span_diagnostic.span_err(attr.span,
+                                        "the `timeout` attribute must be of the form \
+                                         `#[timeout = \"<seconds>\"]` with a positive \
+                                         number of seconds");
+            None
+        }
+    }
+}
+
 /*
 
 We're going to be building a module that looks more or less like:
@@ -662,13 +681,22 @@ fn mk_test_desc_and_fn_rec(cx: &TestCtxt, test: &Test) -> P<ast::Expr> {
         }
     };
 
+    let timeout_expr = match test.timeout {
+        Some(secs) => {
+            let lit_ty = ast::LitIntType::Unsigned(ast::UintTy::U64);
+            ecx.expr_some(span, ecx.expr_lit(span, ast::LitKind::Int(secs, lit_ty)))
+        }
+        None => ecx.expr_none(span),
+    };
+
     // self::test::TestDesc { ... }
     let desc_expr = ecx.expr_struct(
         span,
         test_path("TestDesc"),
         vec![field("name", name_expr),
              field("ignore", ignore_expr),
-             field("should_panic", fail_expr)]);
+             field("should_panic", fail_expr),
+             field("timeout", timeout_expr)]);
 
 
     let mut visible_path = match cx.toplevel_reexport {
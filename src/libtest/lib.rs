@@ -68,19 +68,35 @@ use std::io::prelude::*;
 use std::io;
 use std::iter::repeat;
 use std::path::PathBuf;
-use std::sync::mpsc::{channel, Sender};
+use std::process;
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Instant, Duration};
 
 const TEST_WARN_TIMEOUT_S: u64 = 60;
+const TEST_TIME_WARN_MS: u64 = 500;
+const TEST_TIME_CRITICAL_MS: u64 = 1000;
+
+// Set by `--isolate` in the environment of the child process that runs a
+// single test, to the name of that test.
+const ISOLATED_TEST_ENV: &'static str = "__RUST_TEST_ISOLATED";
+// The exit code of an isolated child process whose test failed.
+const ISOLATED_TEST_FAILED: i32 = 101;
+
+// The number of samples `Bencher::auto_bench` summarizes.
+const BENCH_SAMPLE_COUNT: usize = 50;
+// Changes in a benchmark's median smaller than this percentage are treated
+// as noise when comparing against a baseline, even if they are
+// statistically significant.
+const BENCH_NOISE_PCT: f64 = 2.0;
 
 // to be used by rustc to compile tests in libtest
 pub mod test {
     pub use {Bencher, TestName, TestResult, TestDesc, TestDescAndFn, TestOpts, TrFailed,
              TrIgnored, TrOk, Metric, MetricMap, StaticTestFn, StaticTestName, DynTestName,
              DynTestFn, run_test, test_main, test_main_static, filter_tests, parse_opts,
-             StaticBenchFn, ShouldPanic};
+             StaticBenchFn, ShouldPanic, OutputFormat};
 }
 
 pub mod stats;
@@ -200,6 +216,9 @@ pub struct TestDesc {
     pub name: TestName,
     pub ignore: bool,
     pub should_panic: ShouldPanic,
+    /// The number of seconds after which the test is considered hung and
+    /// reported as failed, overriding `--test-timeout`.
+    pub timeout: Option<u64>,
 }
 
 #[derive(Clone)]
@@ -248,9 +267,15 @@ pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>) {
         Some(Err(msg)) => panic!("{:?}", msg),
         None => return,
     };
+    if opts.list {
+        if let Err(e) = list_tests_console(&opts, tests) {
+            panic!("io error when listing tests: {:?}", e);
+        }
+        return;
+    }
     match run_tests_console(&opts, tests) {
         Ok(true) => {}
-        Ok(false) => std::process::exit(101),
+        Ok(false) => process::exit(101),
         Err(e) => panic!("io error when running tests: {:?}", e),
     }
 }
@@ -293,31 +318,91 @@ pub enum ColorConfig {
     NeverColor,
 }
 
+/// How the console test runner reports its progress.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OutputFormat {
+    /// One line per test (the default).
+    Pretty,
+    /// One character per test.
+    Terse,
+    /// One JSON object per line for every event, for consumption by tools.
+    Json,
+}
+
+/// The execution times above which `--report-time` highlights a test.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TimeThresholds {
+    pub warn: Duration,
+    pub critical: Duration,
+}
+
+impl TimeThresholds {
+    pub fn new(warn: Duration, critical: Duration) -> TimeThresholds {
+        TimeThresholds {
+            warn: warn,
+            critical: critical,
+        }
+    }
+}
+
+impl Default for TimeThresholds {
+    fn default() -> TimeThresholds {
+        TimeThresholds::new(Duration::from_millis(TEST_TIME_WARN_MS),
+                            Duration::from_millis(TEST_TIME_CRITICAL_MS))
+    }
+}
+
 pub struct TestOpts {
-    pub filter: Option<String>,
+    pub list: bool,
+    pub filters: Vec<String>,
+    pub filter_exact: bool,
+    pub skip: Vec<String>,
     pub run_ignored: bool,
     pub run_tests: bool,
     pub bench_benchmarks: bool,
     pub logfile: Option<PathBuf>,
+    pub junit: Option<PathBuf>,
     pub nocapture: bool,
     pub color: ColorConfig,
-    pub quiet: bool,
+    pub format: OutputFormat,
     pub test_threads: Option<usize>,
+    pub test_timeout: Option<u64>,
+    pub report_time: Option<TimeThresholds>,
+    pub isolate: bool,
+    /// Run the tests in an order shuffled with this seed.
+    pub shuffle_seed: Option<u64>,
+    /// Only run the tests in shard `index` (counting from 1) of `count`.
+    pub shard: Option<(usize, usize)>,
+    /// Compare benchmark results against the ones saved in this file.
+    pub baseline: Option<PathBuf>,
+    /// Save benchmark results to this file.
+    pub save_baseline: Option<PathBuf>,
 }
 
 impl TestOpts {
     #[cfg(test)]
     fn new() -> TestOpts {
         TestOpts {
-            filter: None,
+            list: false,
+            filters: Vec::new(),
+            filter_exact: false,
+            skip: Vec::new(),
             run_ignored: false,
             run_tests: false,
             bench_benchmarks: false,
             logfile: None,
+            junit: None,
             nocapture: false,
             color: AutoColor,
-            quiet: false,
+            format: OutputFormat::Pretty,
             test_threads: None,
+            test_timeout: None,
+            report_time: None,
+            isolate: false,
+            shuffle_seed: None,
+            shard: None,
+            baseline: None,
+            save_baseline: None,
         }
     }
 }
@@ -328,33 +413,84 @@ pub type OptRes = Result<TestOpts, String>;
 #[cfg_attr(rustfmt, rustfmt_skip)]
 fn optgroups() -> Vec<getopts::OptGroup> {
     vec!(getopts::optflag("", "ignored", "Run ignored tests"),
+      getopts::optflag("", "list", "List all tests and benchmarks without running them"),
+      getopts::optflag("", "exact", "Only run tests whose names exactly match a filter"),
+      getopts::optmulti("", "skip", "Skip tests whose names contain FILTER (this flag can \
+                                     be used multiple times)", "FILTER"),
       getopts::optflag("", "test", "Run tests and not benchmarks"),
       getopts::optflag("", "bench", "Run benchmarks instead of tests"),
       getopts::optflag("h", "help", "Display this message (longer with --help)"),
       getopts::optopt("", "logfile", "Write logs to the specified file instead \
                           of stdout", "PATH"),
+      getopts::optopt("", "junit", "Write a JUnit XML report of the test run to the \
+                                    specified file", "PATH"),
       getopts::optflag("", "nocapture", "don't capture stdout/stderr of each \
                                          task, allow printing directly"),
       getopts::optopt("", "test-threads", "Number of threads used for running tests \
                                            in parallel", "n_threads"),
-      getopts::optflag("q", "quiet", "Display one character per test instead of one line"),
+      getopts::optopt("", "test-timeout", "Fail any test that runs for longer than the given \
+                                           number of seconds", "SECS"),
+      getopts::optflag("", "report-time", "Show the execution time of each test"),
+      getopts::optopt("", "time-thresholds", "Execution times (in milliseconds) above which \
+                                              --report-time highlights a test as slow or \
+                                              very slow (default: 500,1000); implies \
+                                              --report-time", "WARN,CRITICAL"),
+      getopts::optflag("", "isolate", "Run each test in its own process, so that crashes \
+                                       and aborts only fail that test"),
+      getopts::optflag("", "shuffle", "Run tests in a random order"),
+      getopts::optopt("", "shuffle-seed", "Run tests in the random order given by SEED \
+                                           (implies --shuffle)", "SEED"),
+      getopts::optopt("", "shard", "Split the tests into COUNT shards and only run shard \
+                                    INDEX, counting from 1", "INDEX/COUNT"),
+      getopts::optopt("", "save-baseline", "Save the benchmark results to PATH, for later \
+                                            comparison with --baseline", "PATH"),
+      getopts::optopt("", "baseline", "Compare the benchmark results against those saved in \
+                                       PATH by --save-baseline", "PATH"),
+      getopts::optflag("q", "quiet", "Display one character per test instead of one line \
+                                      (alias for --format=terse)"),
       getopts::optopt("", "color", "Configure coloring of output:
             auto   = colorize if stdout is a tty and tests are run on serially (default);
             always = always colorize output;
-            never  = never colorize output;", "auto|always|never"))
+            never  = never colorize output;", "auto|always|never"),
+      getopts::optopt("", "format", "Configure formatting of output:
+            pretty = print verbose output (default);
+            terse  = display one character per test;
+            json   = output one JSON object per event;", "pretty|terse|json"))
 }
 
 fn usage(binary: &str) {
-    let message = format!("Usage: {} [OPTIONS] [FILTER]", binary);
+    let message = format!("Usage: {} [OPTIONS] [FILTER...]", binary);
     println!(r#"{usage}
 
-The FILTER string is tested against the name of all tests, and only those
-tests whose names contain the filter are run.
+Each FILTER string is tested against the name of all tests, and only those
+tests whose names contain at least one of the filters are run. With --exact,
+a test's name has to be equal to one of the filters instead. Tests whose
+names match a --skip filter are not run, whether or not they match a FILTER.
+
+The --list flag prints the name of every test and benchmark that would be run,
+taking the filters and --ignored into account, without running any of them.
+
+Benchmark results can be saved with --save-baseline and compared against by a
+later run with --baseline. The comparison shows the change in the median time
+per iteration together with a 95% confidence interval for it. A benchmark is
+reported as regressed or improved when that interval doesn't include zero and
+the change is larger than {noise}%.
+
+With --shuffle, tests are started in a random order to expose tests which
+depend on each other. The seed for that order is printed when the run starts
+and can be passed to --shuffle-seed to repeat it. --shard splits the tests into
+the same shards every time, regardless of the order they are run in, so that
+a test binary can be spread over several machines.
 
 By default, all tests are run in parallel. This can be altered with the
 --test-threads flag or the RUST_TEST_THREADS environment variable when running
 tests (set it to 1).
 
+With --isolate, the test binary runs itself again for every test, so a test
+that aborts, crashes or deadlocks the process fails on its own instead of
+taking down the whole run. This also makes tests built with panic=abort
+report their panics as test failures.
+
 All tests have their standard output and standard error captured by default.
 This can be overridden with the --nocapture flag or setting RUST_TEST_NOCAPTURE
 environment variable to a value other than "0". Logging is not captured by default.
@@ -372,8 +508,11 @@ Test Attributes:
     #[ignore]      - When applied to a function which is already attributed as a
                      test, then the test runner will ignore these tests during
                      normal test runs. Running with --ignored will run these
-                     tests."#,
-             usage = getopts::usage(&message, &optgroups()));
+                     tests.
+    #[timeout = "secs"] - The test fails if it is still running after the given
+                     number of seconds. This overrides --test-timeout."#,
+             usage = getopts::usage(&message, &optgroups()),
+             noise = BENCH_NOISE_PCT);
 }
 
 // Parses command line arguments into test options
@@ -389,18 +528,19 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
         return None;
     }
 
-    let filter = if !matches.free.is_empty() {
-        Some(matches.free[0].clone())
-    } else {
-        None
-    };
+    let filters = matches.free.clone();
+    let filter_exact = matches.opt_present("exact");
+    let skip = matches.opt_strs("skip");
+    let list = matches.opt_present("list");
 
     let run_ignored = matches.opt_present("ignored");
-    let quiet = matches.opt_present("quiet");
 
     let logfile = matches.opt_str("logfile");
     let logfile = logfile.map(|s| PathBuf::from(&s));
 
+    let junit = matches.opt_str("junit");
+    let junit = junit.map(|s| PathBuf::from(&s));
+
     let bench_benchmarks = matches.opt_present("bench");
     let run_tests = !bench_benchmarks || matches.opt_present("test");
 
@@ -424,6 +564,68 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
             None,
     };
 
+    let test_timeout = match matches.opt_str("test-timeout") {
+        Some(secs) => {
+            match secs.parse::<u64>() {
+                Ok(n) if n > 0 => Some(n),
+                _ => {
+                    return Some(Err(format!("argument for --test-timeout must be a positive \
+                                             number of seconds (was {})",
+                                            secs)))
+                }
+            }
+        }
+        None => None,
+    };
+
+    let report_time = match matches.opt_str("time-thresholds") {
+        Some(thresholds) => {
+            match parse_time_thresholds(&thresholds) {
+                Some(t) => Some(t),
+                None => {
+                    return Some(Err(format!("argument for --time-thresholds must be of the \
+                                             form WARN,CRITICAL in milliseconds with WARN <= \
+                                             CRITICAL (was {})",
+                                            thresholds)))
+                }
+            }
+        }
+        None if matches.opt_present("report-time") => Some(TimeThresholds::default()),
+        None => None,
+    };
+
+    let shuffle_seed = match matches.opt_str("shuffle-seed") {
+        Some(seed) => {
+            match seed.parse::<u64>() {
+                Ok(seed) => Some(seed),
+                Err(e) => {
+                    return Some(Err(format!("argument for --shuffle-seed must be a number \
+                                             (error: {})",
+                                            e)))
+                }
+            }
+        }
+        None if matches.opt_present("shuffle") => Some(random_seed()),
+        None => None,
+    };
+
+    let shard = match matches.opt_str("shard") {
+        Some(shard) => {
+            match parse_shard(&shard) {
+                Some(shard) => Some(shard),
+                None => {
+                    return Some(Err(format!("argument for --shard must be of the form \
+                                             INDEX/COUNT with 1 <= INDEX <= COUNT (was {})",
+                                            shard)))
+                }
+            }
+        }
+        None => None,
+    };
+
+    let baseline = matches.opt_str("baseline").map(|s| PathBuf::from(&s));
+    let save_baseline = matches.opt_str("save-baseline").map(|s| PathBuf::from(&s));
+
     let color = match matches.opt_str("color").as_ref().map(|s| &**s) {
         Some("auto") | None => AutoColor,
         Some("always") => AlwaysColor,
@@ -436,21 +638,75 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
         }
     };
 
+    let format = match matches.opt_str("format").as_ref().map(|s| &**s) {
+        None if matches.opt_present("quiet") => OutputFormat::Terse,
+        Some("pretty") | None => OutputFormat::Pretty,
+        Some("terse") => OutputFormat::Terse,
+        Some("json") => OutputFormat::Json,
+
+        Some(v) => {
+            return Some(Err(format!("argument for --format must be pretty, terse, or json (was \
+                                     {})",
+                                    v)))
+        }
+    };
+
     let test_opts = TestOpts {
-        filter: filter,
+        list: list,
+        filters: filters,
+        filter_exact: filter_exact,
+        skip: skip,
         run_ignored: run_ignored,
         run_tests: run_tests,
         bench_benchmarks: bench_benchmarks,
         logfile: logfile,
+        junit: junit,
         nocapture: nocapture,
         color: color,
-        quiet: quiet,
+        format: format,
         test_threads: test_threads,
+        test_timeout: test_timeout,
+        report_time: report_time,
+        isolate: matches.opt_present("isolate"),
+        shuffle_seed: shuffle_seed,
+        shard: shard,
+        baseline: baseline,
+        save_baseline: save_baseline,
     };
 
     Some(Ok(test_opts))
 }
 
+fn parse_shard(s: &str) -> Option<(usize, usize)> {
+    let mut parts = s.splitn(2, '/');
+    let index = parts.next().and_then(|n| n.trim().parse::<usize>().ok());
+    let count = parts.next().and_then(|n| n.trim().parse::<usize>().ok());
+    match (index, count) {
+        (Some(index), Some(count)) if 1 <= index && index <= count => Some((index, count)),
+        _ => None,
+    }
+}
+
+// Picks a seed for `--shuffle` when none was given
+fn random_seed() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::new(0, 0));
+    now.as_secs().wrapping_mul(1_000_000_000).wrapping_add(now.subsec_nanos() as u64)
+}
+
+fn parse_time_thresholds(s: &str) -> Option<TimeThresholds> {
+    let mut parts = s.splitn(2, ',');
+    let warn = parts.next().and_then(|n| n.trim().parse::<u64>().ok());
+    let critical = parts.next().and_then(|n| n.trim().parse::<u64>().ok());
+    match (warn, critical) {
+        (Some(warn), Some(critical)) if warn <= critical => {
+            Some(TimeThresholds::new(Duration::from_millis(warn),
+                                     Duration::from_millis(critical)))
+        }
+        _ => None,
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub struct BenchSamples {
     ns_iter_summ: stats::Summary,
@@ -473,11 +729,43 @@ enum OutputLocation<T> {
     Raw(T),
 }
 
+// The test results collected for the JUnit report, which is written out in
+// one go once the run has finished.
+struct JunitReport {
+    out: File,
+    start: Instant,
+    cases: Vec<(TestDesc, TestResult, Duration, Vec<u8>)>,
+}
+
+// The benchmark results loaded with `--baseline` and those to be saved with
+// `--save-baseline`.
+struct BenchBaselines {
+    old: BTreeMap<String, BenchBaseline>,
+    new: BTreeMap<String, BenchBaseline>,
+    save_path: Option<PathBuf>,
+    regressions: Vec<String>,
+}
+
+impl BenchBaselines {
+    fn new() -> BenchBaselines {
+        BenchBaselines {
+            old: BTreeMap::new(),
+            new: BTreeMap::new(),
+            save_path: None,
+            regressions: Vec::new(),
+        }
+    }
+}
+
 struct ConsoleTestState<T> {
     log_out: Option<File>,
+    junit: Option<JunitReport>,
+    baselines: BenchBaselines,
     out: OutputLocation<T>,
     use_color: bool,
-    quiet: bool,
+    format: OutputFormat,
+    report_time: Option<TimeThresholds>,
+    shuffle_seed: Option<u64>,
     total: usize,
     passed: usize,
     failed: usize,
@@ -494,6 +782,29 @@ impl<T: Write> ConsoleTestState<T> {
             Some(ref path) => Some(File::create(path)?),
             None => None,
         };
+        let junit = match opts.junit {
+            Some(ref path) => {
+                Some(JunitReport {
+                    out: File::create(path)?,
+                    start: Instant::now(),
+                    cases: Vec::new(),
+                })
+            }
+            None => None,
+        };
+        let mut baselines = BenchBaselines::new();
+        if let Some(ref path) = opts.baseline {
+            let mut contents = String::new();
+            File::open(path)?.read_to_string(&mut contents)?;
+            baselines.old = match parse_bench_baselines(&contents) {
+                Ok(old) => old,
+                Err(msg) => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                              format!("{}: {}", path.display(), msg)))
+                }
+            };
+        }
+        baselines.save_path = opts.save_baseline.clone();
         let out = match term::stdout() {
             None => Raw(io::stdout()),
             Some(t) => Pretty(t),
@@ -502,8 +813,12 @@ impl<T: Write> ConsoleTestState<T> {
         Ok(ConsoleTestState {
             out: out,
             log_out: log_out,
-            use_color: use_color(opts),
-            quiet: opts.quiet,
+            junit: junit,
+            baselines: baselines,
+            use_color: opts.format != OutputFormat::Json && use_color(opts),
+            format: opts.format,
+            report_time: opts.report_time,
+            shuffle_seed: opts.shuffle_seed,
             total: 0,
             passed: 0,
             failed: 0,
@@ -515,16 +830,16 @@ impl<T: Write> ConsoleTestState<T> {
         })
     }
 
-    pub fn write_ok(&mut self) -> io::Result<()> {
-        self.write_short_result("ok", ".", term::color::GREEN)
+    pub fn write_ok(&mut self, exec_time: Option<Duration>) -> io::Result<()> {
+        self.write_short_result("ok", ".", term::color::GREEN, exec_time)
     }
 
-    pub fn write_failed(&mut self) -> io::Result<()> {
-        self.write_short_result("FAILED", "F", term::color::RED)
+    pub fn write_failed(&mut self, exec_time: Option<Duration>) -> io::Result<()> {
+        self.write_short_result("FAILED", "F", term::color::RED, exec_time)
     }
 
     pub fn write_ignored(&mut self) -> io::Result<()> {
-        self.write_short_result("ignored", "i", term::color::YELLOW)
+        self.write_short_result("ignored", "i", term::color::YELLOW, None)
     }
 
     pub fn write_metric(&mut self) -> io::Result<()> {
@@ -535,16 +850,35 @@ impl<T: Write> ConsoleTestState<T> {
         self.write_pretty("bench", term::color::CYAN)
     }
 
-    pub fn write_short_result(&mut self, verbose: &str, quiet: &str, color: term::color::Color)
+    pub fn write_short_result(&mut self,
+                              verbose: &str,
+                              quiet: &str,
+                              color: term::color::Color,
+                              exec_time: Option<Duration>)
                               -> io::Result<()> {
-        if self.quiet {
+        if self.format == OutputFormat::Terse {
             self.write_pretty(quiet, color)
         } else {
             self.write_pretty(verbose, color)?;
+            if let Some(exec_time) = exec_time {
+                self.write_time(exec_time)?;
+            }
             self.write_plain("\n")
         }
     }
 
+    pub fn write_time(&mut self, exec_time: Duration) -> io::Result<()> {
+        let time = format!(" <{}.{:03}s>",
+                           exec_time.as_secs(),
+                           exec_time.subsec_nanos() / 1_000_000);
+        let thresholds = self.report_time;
+        match thresholds {
+            Some(t) if exec_time >= t.critical => self.write_pretty(&time, term::color::RED),
+            Some(t) if exec_time >= t.warn => self.write_pretty(&time, term::color::YELLOW),
+            _ => self.write_plain(&time),
+        }
+    }
+
     pub fn write_pretty(&mut self, word: &str, color: term::color::Color) -> io::Result<()> {
         match self.out {
             Pretty(ref mut term) => {
@@ -579,27 +913,60 @@ impl<T: Write> ConsoleTestState<T> {
 
     pub fn write_run_start(&mut self, len: usize) -> io::Result<()> {
         self.total = len;
+        if self.format == OutputFormat::Json {
+            let seed = match self.shuffle_seed {
+                Some(seed) => format!(", \"shuffle_seed\": {}", seed),
+                None => String::new(),
+            };
+            return self.write_plain(&format!("{{ \"type\": \"suite\", \"event\": \"started\", \
+                                              \"test_count\": {}{} }}\n",
+                                             len,
+                                             seed));
+        }
         let noun = if len != 1 {
             "tests"
         } else {
             "test"
         };
-        self.write_plain(&format!("\nrunning {} {}\n", len, noun))
+        self.write_plain(&format!("\nrunning {} {}\n", len, noun))?;
+        match self.shuffle_seed {
+            Some(seed) => self.write_plain(&format!("shuffle seed: {}\n", seed)),
+            None => Ok(()),
+        }
     }
 
     pub fn write_test_start(&mut self, test: &TestDesc, align: NamePadding) -> io::Result<()> {
-        if self.quiet && align != PadOnRight {
-            Ok(())
-        } else {
-            let name = test.padded_name(self.max_name_len, align);
-            self.write_plain(&format!("test {} ... ", name))
+        match self.format {
+            OutputFormat::Json => {
+                self.write_plain(&format!("{{ \"type\": \"test\", \"event\": \"started\", \
+                                           \"name\": \"{}\" }}\n",
+                                          json_escape(test.name.as_slice())))
+            }
+            OutputFormat::Terse if align != PadOnRight => Ok(()),
+            _ => {
+                let name = test.padded_name(self.max_name_len, align);
+                self.write_plain(&format!("test {} ... ", name))
+            }
         }
     }
 
-    pub fn write_result(&mut self, result: &TestResult) -> io::Result<()> {
+    pub fn write_result(&mut self,
+                        test: &TestDesc,
+                        result: &TestResult,
+                        exec_time: Duration,
+                        stdout: &[u8])
+                        -> io::Result<()> {
+        if self.format == OutputFormat::Json {
+            return self.write_json_result(test, result, exec_time, stdout);
+        }
+        let exec_time = if self.report_time.is_some() {
+            Some(exec_time)
+        } else {
+            None
+        };
         match *result {
-            TrOk => self.write_ok(),
-            TrFailed => self.write_failed(),
+            TrOk => self.write_ok(exec_time),
+            TrFailed => self.write_failed(exec_time),
             TrIgnored => self.write_ignored(),
             TrMetrics(ref mm) => {
                 self.write_metric()?;
@@ -607,12 +974,110 @@ impl<T: Write> ConsoleTestState<T> {
             }
             TrBench(ref bs) => {
                 self.write_bench()?;
-                self.write_plain(&format!(": {}\n", fmt_bench_samples(bs)))
+                self.write_plain(&format!(": {}", fmt_bench_samples(bs)))?;
+                if let Some(change) = self.bench_change(test, bs) {
+                    self.write_plain(&format!(" change: {}", fmt_bench_change(&change)))?;
+                    if change.is_regression() {
+                        self.write_pretty(" regressed", term::color::RED)?;
+                    } else if change.is_improvement() {
+                        self.write_pretty(" improved", term::color::GREEN)?;
+                    }
+                }
+                self.write_plain("\n")
+            }
+        }
+    }
+
+    fn write_json_result(&mut self,
+                         test: &TestDesc,
+                         result: &TestResult,
+                         exec_time: Duration,
+                         stdout: &[u8])
+                         -> io::Result<()> {
+        let name = json_escape(test.name.as_slice());
+        let stdout = if stdout.is_empty() {
+            String::new()
+        } else {
+            format!(", \"stdout\": \"{}\"", json_escape(&String::from_utf8_lossy(stdout)))
+        };
+        let line = match *result {
+            TrOk | TrFailed | TrIgnored => {
+                let event = match *result {
+                    TrOk => "ok",
+                    TrFailed => "failed",
+                    _ => "ignored",
+                };
+                format!("{{ \"type\": \"test\", \"event\": \"{}\", \"name\": \"{}\", \
+                         \"exec_time\": {}{} }}",
+                        event,
+                        name,
+                        fmt_duration_secs(exec_time),
+                        stdout)
+            }
+            TrMetrics(ref mm) => {
+                format!("{{ \"type\": \"test\", \"event\": \"metrics\", \"name\": \"{}\", \
+                         \"metrics\": {}{} }}",
+                        name,
+                        mm.fmt_metrics_json(),
+                        stdout)
+            }
+            TrBench(ref bs) => {
+                let change = match self.bench_change(test, bs) {
+                    Some(change) => {
+                        format!(", \"change\": {{ \"median_pct\": {}, \"lower_pct\": {}, \
+                                 \"upper_pct\": {}, \"regressed\": {}, \"improved\": {} }}",
+                                json_f64(change.median_pct),
+                                json_f64(change.lower_pct),
+                                json_f64(change.upper_pct),
+                                change.is_regression(),
+                                change.is_improvement())
+                    }
+                    None => String::new(),
+                };
+                format!("{{ \"type\": \"bench\", \"name\": \"{}\", {}{}{} }}",
+                        name,
+                        fmt_bench_samples_json(bs),
+                        change,
+                        stdout)
+            }
+        };
+        self.write_plain(&format!("{}\n", line))
+    }
+
+    // The change of a benchmark relative to its result in the baseline, if
+    // it has one.
+    fn bench_change(&self, test: &TestDesc, bs: &BenchSamples) -> Option<BenchChange> {
+        self.baselines
+            .old
+            .get(test.name.as_slice())
+            .map(|old| compare_to_baseline(old, &BenchBaseline::from_samples(bs)))
+    }
+
+    pub fn record_bench(&mut self, test: &TestDesc, bs: &BenchSamples) {
+        if let Some(change) = self.bench_change(test, bs) {
+            if change.is_regression() {
+                self.baselines.regressions.push(test.name.to_string());
+            }
+        }
+        self.baselines.new.insert(test.name.to_string(), BenchBaseline::from_samples(bs));
+    }
+
+    pub fn write_baseline(&mut self) -> io::Result<()> {
+        match self.baselines.save_path {
+            None => Ok(()),
+            Some(ref path) => {
+                let mut out = File::create(path)?;
+                out.write_all(fmt_bench_baselines(&self.baselines.new).as_bytes())
             }
         }
     }
 
     pub fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
+        if self.format == OutputFormat::Json {
+            return self.write_plain(&format!("{{ \"type\": \"test\", \"event\": \"timeout\", \
+                                              \"name\": \"{}\" }}\n",
+                                             json_escape(desc.name.as_slice())));
+        }
         self.write_plain(&format!("test {} has been running for over {} seconds\n",
                                   desc.name,
                                   TEST_WARN_TIMEOUT_S))
@@ -636,6 +1101,32 @@ impl<T: Write> ConsoleTestState<T> {
         }
     }
 
+    pub fn record_junit(&mut self,
+                        test: &TestDesc,
+                        result: &TestResult,
+                        exec_time: Duration,
+                        stdout: &[u8]) {
+        if let Some(ref mut junit) = self.junit {
+            junit.cases.push((test.clone(), result.clone(), exec_time, stdout.to_vec()));
+        }
+    }
+
+    pub fn write_junit(&mut self) -> io::Result<()> {
+        match self.junit {
+            None => Ok(()),
+            Some(ref mut junit) => {
+                let suite = env::current_exe()
+                                .ok()
+                                .and_then(|p| {
+                                    p.file_stem().map(|s| s.to_string_lossy().into_owned())
+                                })
+                                .unwrap_or_else(|| "test".to_owned());
+                let report = fmt_junit_report(&suite, &junit.cases, junit.start.elapsed());
+                junit.out.write_all(report.as_bytes())
+            }
+        }
+    }
+
     pub fn write_failures(&mut self) -> io::Result<()> {
         self.write_plain("\nfailures:\n")?;
         let mut failures = Vec::new();
@@ -659,17 +1150,52 @@ impl<T: Write> ConsoleTestState<T> {
         for name in &failures {
             self.write_plain(&format!("    {}\n", name))?;
         }
+        if let Some(seed) = self.shuffle_seed {
+            self.write_plain(&format!("\nthe tests were run in a shuffled order; to run them \
+                                       in the same order again, pass `--shuffle-seed {}`\n",
+                                      seed))?;
+        }
         Ok(())
     }
 
     pub fn write_run_finish(&mut self) -> io::Result<bool> {
         assert!(self.passed + self.failed + self.ignored + self.measured == self.total);
 
+        self.write_junit()?;
+        self.write_baseline()?;
+
         let success = self.failed == 0;
+        if self.format == OutputFormat::Json {
+            let regressions = if self.baselines.old.is_empty() {
+                String::new()
+            } else {
+                format!(", \"regressions\": {}", self.baselines.regressions.len())
+            };
+            let s = format!("{{ \"type\": \"suite\", \"event\": \"{}\", \"passed\": {}, \
+                             \"failed\": {}, \"ignored\": {}, \"measured\": {}{} }}\n",
+                            if success { "ok" } else { "failed" },
+                            self.passed,
+                            self.failed,
+                            self.ignored,
+                            self.measured,
+                            regressions);
+            self.write_plain(&s)?;
+            return Ok(success);
+        }
+
         if !success {
             self.write_failures()?;
         }
 
+        if !self.baselines.regressions.is_empty() {
+            self.write_plain("\nbenchmarks that regressed compared to the baseline:\n")?;
+            let mut regressions = self.baselines.regressions.clone();
+            regressions.sort();
+            for name in &regressions {
+                self.write_plain(&format!("    {}\n", name))?;
+            }
+        }
+
         self.write_plain("\ntest result: ")?;
         if success {
             // There's no parallelism at this point so it's safe to use color
@@ -728,17 +1254,341 @@ pub fn fmt_bench_samples(bs: &BenchSamples) -> String {
     output
 }
 
+/// A benchmark result, as saved by `--save-baseline`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BenchBaseline {
+    /// The median time per iteration, in nanoseconds.
+    pub median: f64,
+    /// The median absolute deviation of the time per iteration, scaled to
+    /// estimate its standard deviation.
+    pub median_abs_dev: f64,
+    /// The number of samples the median was taken of.
+    pub samples: usize,
+}
+
+impl BenchBaseline {
+    pub fn from_samples(bs: &BenchSamples) -> BenchBaseline {
+        BenchBaseline {
+            median: bs.ns_iter_summ.median,
+            median_abs_dev: bs.ns_iter_summ.median_abs_dev,
+            samples: BENCH_SAMPLE_COUNT,
+        }
+    }
+}
+
+/// The change of a benchmark's median time per iteration relative to a
+/// baseline, as percentages of the baseline's median. `lower_pct` and
+/// `upper_pct` are the bounds of the 95% confidence interval of the change.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BenchChange {
+    pub median_pct: f64,
+    pub lower_pct: f64,
+    pub upper_pct: f64,
+}
+
+impl BenchChange {
+    /// Whether the benchmark got significantly slower.
+    pub fn is_regression(&self) -> bool {
+        self.lower_pct > 0.0 && self.median_pct > BENCH_NOISE_PCT
+    }
+
+    /// Whether the benchmark got significantly faster.
+    pub fn is_improvement(&self) -> bool {
+        self.upper_pct < 0.0 && self.median_pct < -BENCH_NOISE_PCT
+    }
+}
+
+/// Compares a benchmark result against its baseline.
+///
+/// The standard error of the median of `n` samples is about
+/// `1.2533 * sigma / sqrt(n)`. The median absolute deviation is used to
+/// estimate `sigma`, as it isn't thrown off by the occasional outlier the way
+/// the standard deviation is.
+pub fn compare_to_baseline(old: &BenchBaseline, new: &BenchBaseline) -> BenchChange {
+    fn std_err(b: &BenchBaseline) -> f64 {
+        1.2533 * b.median_abs_dev / (cmp::max(b.samples, 1) as f64).sqrt()
+    }
+
+    let diff = new.median - old.median;
+    let margin = 1.96 * (std_err(old).powi(2) + std_err(new).powi(2)).sqrt();
+    // A benchmark can take less than a nanosecond per iteration
+    let base = old.median.max(1.0);
+    BenchChange {
+        median_pct: diff / base * 100.0,
+        lower_pct: (diff - margin) / base * 100.0,
+        upper_pct: (diff + margin) / base * 100.0,
+    }
+}
+
+pub fn fmt_bench_change(change: &BenchChange) -> String {
+    format!("{:+.2}% [{:+.2}%, {:+.2}%]",
+            change.median_pct,
+            change.lower_pct,
+            change.upper_pct)
+}
+
+const BENCH_BASELINE_HEADER: &'static str = "# libtest benchmark baseline v1";
+
+// Baselines are saved one benchmark per line, as tab-separated fields with the
+// name last, so that it can contain anything but a newline.
+fn fmt_bench_baselines(baselines: &BTreeMap<String, BenchBaseline>) -> String {
+    use std::fmt::Write;
+    let mut output = String::new();
+    output.push_str(BENCH_BASELINE_HEADER);
+    output.push('\n');
+    for (name, b) in baselines {
+        output.write_fmt(format_args!("{}\t{}\t{}\t{}\n",
+                                      b.median,
+                                      b.median_abs_dev,
+                                      b.samples,
+                                      name))
+              .unwrap();
+    }
+    output
+}
+
+fn parse_bench_baselines(s: &str) -> Result<BTreeMap<String, BenchBaseline>, String> {
+    let mut lines = s.lines();
+    if lines.next() != Some(BENCH_BASELINE_HEADER) {
+        return Err("not a benchmark baseline file".to_owned());
+    }
+
+    let mut baselines = BTreeMap::new();
+    for (i, line) in lines.enumerate() {
+        let mut fields = line.splitn(4, '\t');
+        let median = fields.next().and_then(|f| f.parse::<f64>().ok());
+        let median_abs_dev = fields.next().and_then(|f| f.parse::<f64>().ok());
+        let samples = fields.next().and_then(|f| f.parse::<usize>().ok());
+        let name = fields.next();
+        match (median, median_abs_dev, samples, name) {
+            (Some(median), Some(median_abs_dev), Some(samples), Some(name)) => {
+                baselines.insert(name.to_owned(),
+                                 BenchBaseline {
+                                     median: median,
+                                     median_abs_dev: median_abs_dev,
+                                     samples: samples,
+                                 });
+            }
+            _ => return Err(format!("malformed baseline on line {}", i + 2)),
+        }
+    }
+    Ok(baselines)
+}
+
+/// Formats the benchmark samples as the members of a JSON object (without
+/// the enclosing braces).
+pub fn fmt_bench_samples_json(bs: &BenchSamples) -> String {
+    let summ = &bs.ns_iter_summ;
+    format!("\"median\": {}, \"deviation\": {}, \"mb_s\": {}, \"min\": {}, \"max\": {}, \
+             \"mean\": {}, \"std_dev\": {}, \"median_abs_dev\": {}, \
+             \"quartiles\": [{}, {}, {}]",
+            json_f64(summ.median),
+            json_f64(summ.max - summ.min),
+            bs.mb_s,
+            json_f64(summ.min),
+            json_f64(summ.max),
+            json_f64(summ.mean),
+            json_f64(summ.std_dev),
+            json_f64(summ.median_abs_dev),
+            json_f64(summ.quartiles.0),
+            json_f64(summ.quartiles.1),
+            json_f64(summ.quartiles.2))
+}
+
+// Escape a string so that it can be embedded in a JSON string literal
+fn json_escape(s: &str) -> String {
+    use std::fmt::Write;
+    let mut output = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                output.write_fmt(format_args!("\\u{:04x}", c as u32)).unwrap()
+            }
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+// JSON has no representation for NaN or the infinities
+fn json_f64(n: f64) -> String {
+    if n.is_finite() {
+        format!("{}", n)
+    } else {
+        "null".to_owned()
+    }
+}
+
+// Format a duration as a number of seconds
+fn fmt_duration_secs(d: Duration) -> String {
+    format!("{}.{:09}", d.as_secs(), d.subsec_nanos())
+}
+
+/// Renders a JUnit XML document describing the given test results.
+pub fn fmt_junit_report(suite: &str,
+                        cases: &[(TestDesc, TestResult, Duration, Vec<u8>)],
+                        total_time: Duration)
+                        -> String {
+    use std::fmt::Write;
+    let mut output = String::new();
+
+    let failures = cases.iter().filter(|&&(_, ref r, _, _)| *r == TrFailed).count();
+    let skipped = cases.iter().filter(|&&(_, ref r, _, _)| *r == TrIgnored).count();
+
+    output.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    output.write_fmt(format_args!("<testsuites>\n<testsuite name=\"{}\" tests=\"{}\" \
+                                   failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{}\">\n",
+                                  xml_escape(suite),
+                                  cases.len(),
+                                  failures,
+                                  skipped,
+                                  fmt_duration_secs(total_time)))
+          .unwrap();
+
+    for &(ref desc, ref result, exec_time, ref stdout) in cases {
+        // Split `a::b::c` into the class `a::b` and the test name `c`, which
+        // is how most JUnit consumers group their results.
+        let full_name = desc.name.as_slice();
+        let (class_name, name) = match full_name.rfind("::") {
+            Some(i) => (&full_name[..i], &full_name[i + 2..]),
+            None => (suite, full_name),
+        };
+        output.write_fmt(format_args!("<testcase classname=\"{}\" name=\"{}\" time=\"{}\"",
+                                      xml_escape(class_name),
+                                      xml_escape(name),
+                                      fmt_duration_secs(exec_time)))
+              .unwrap();
+
+        let stdout = String::from_utf8_lossy(stdout);
+        match *result {
+            TrOk if stdout.is_empty() => output.push_str("/>\n"),
+            TrOk => {
+                output.write_fmt(format_args!("><system-out>{}</system-out></testcase>\n",
+                                              xml_escape(&stdout)))
+                      .unwrap()
+            }
+            TrFailed => {
+                // The panic message is the most useful summary of a failure
+                // we have, so use it if the test printed one.
+                let message = stdout.lines()
+                                    .find(|l| l.contains("panicked at"))
+                                    .unwrap_or("test failed");
+                output.write_fmt(format_args!("><failure message=\"{}\" type=\"panic\">{}\
+                                               </failure></testcase>\n",
+                                              xml_escape(message),
+                                              xml_escape(&stdout)))
+                      .unwrap()
+            }
+            TrIgnored => output.push_str("><skipped/></testcase>\n"),
+            TrMetrics(ref mm) => {
+                output.write_fmt(format_args!("><system-out>{}</system-out></testcase>\n",
+                                              xml_escape(&mm.fmt_metrics())))
+                      .unwrap()
+            }
+            TrBench(ref bs) => {
+                output.write_fmt(format_args!("><system-out>{}</system-out></testcase>\n",
+                                              xml_escape(fmt_bench_samples(bs).trim())))
+                      .unwrap()
+            }
+        }
+    }
+
+    output.push_str("</testsuite>\n</testsuites>\n");
+    output
+}
+
+// Escape a string so that it can be used as XML text or attribute value
+fn xml_escape(s: &str) -> String {
+    let mut output = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&apos;"),
+            '\n' | '\r' | '\t' => output.push(c),
+            // Other control characters can't be represented in XML 1.0 at all
+            c if (c as u32) < 0x20 => {}
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+// Prints the tests that would be run, without running them
+pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    let mut ntest = 0;
+    let mut nbench = 0;
+    for test in filter_tests(opts, tests) {
+        let kind = match test.testfn {
+            StaticTestFn(..) | DynTestFn(..) => {
+                ntest += 1;
+                "test"
+            }
+            StaticBenchFn(..) | DynBenchFn(..) => {
+                nbench += 1;
+                "bench"
+            }
+            StaticMetricFn(..) | DynMetricFn(..) => "metric",
+        };
+        let name = test.desc.name.as_slice();
+
+        match opts.format {
+            OutputFormat::Pretty => {
+                writeln!(out,
+                         "{}: {}{}",
+                         name,
+                         kind,
+                         if test.desc.ignore { " (ignored)" } else { "" })?
+            }
+            OutputFormat::Terse => writeln!(out, "{}", name)?,
+            OutputFormat::Json => {
+                writeln!(out,
+                         "{{ \"type\": \"{}\", \"event\": \"discovered\", \"name\": \"{}\", \
+                          \"ignored\": {} }}",
+                         kind,
+                         json_escape(name),
+                         test.desc.ignore)?
+            }
+        }
+    }
+
+    if opts.format == OutputFormat::Pretty {
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        writeln!(out,
+                 "\n{} test{}, {} benchmark{}",
+                 ntest,
+                 plural(ntest),
+                 nbench,
+                 plural(nbench))?;
+    }
+    Ok(())
+}
+
 // A simple console test runner
 pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Result<bool> {
+    if let Ok(name) = env::var(ISOLATED_TEST_ENV) {
+        run_isolated_child(tests, &name);
+    }
 
     fn callback<T: Write>(event: &TestEvent, st: &mut ConsoleTestState<T>) -> io::Result<()> {
         match (*event).clone() {
             TeFiltered(ref filtered_tests) => st.write_run_start(filtered_tests.len()),
             TeWait(ref test, padding) => st.write_test_start(test, padding),
             TeTimeout(ref test) => st.write_timeout(test),
-            TeResult(test, result, stdout) => {
+            TeResult(test, result, stdout, exec_time) => {
                 st.write_log(&test, &result)?;
-                st.write_result(&result)?;
+                st.write_result(&test, &result, exec_time, &stdout)?;
+                st.record_junit(&test, &result, exec_time, &stdout);
                 match result {
                     TrOk => st.passed += 1,
                     TrIgnored => st.ignored += 1,
@@ -752,6 +1602,7 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
                         st.measured += 1
                     }
                     TrBench(bs) => {
+                        st.record_bench(&test, &bs);
                         st.metrics.insert_metric(test.name.as_slice(),
                                                  bs.ns_iter_summ.median,
                                                  bs.ns_iter_summ.max - bs.ns_iter_summ.min);
@@ -788,19 +1639,25 @@ fn should_sort_failures_before_printing_them() {
         name: StaticTestName("a"),
         ignore: false,
         should_panic: ShouldPanic::No,
+        timeout: None,
     };
 
     let test_b = TestDesc {
         name: StaticTestName("b"),
         ignore: false,
         should_panic: ShouldPanic::No,
+        timeout: None,
     };
 
     let mut st = ConsoleTestState {
         log_out: None,
+        junit: None,
+        baselines: BenchBaselines::new(),
         out: Raw(Vec::new()),
         use_color: false,
-        quiet: false,
+        format: OutputFormat::Pretty,
+        report_time: None,
+        shuffle_seed: None,
         total: 0,
         passed: 0,
         failed: 0,
@@ -822,6 +1679,46 @@ fn should_sort_failures_before_printing_them() {
     assert!(apos < bpos);
 }
 
+#[test]
+fn json_results_are_escaped_and_include_stdout() {
+    let test = TestDesc {
+        name: StaticTestName("a::\"quoted\""),
+        ignore: false,
+        should_panic: ShouldPanic::No,
+        timeout: None,
+    };
+
+    let mut st = ConsoleTestState {
+        log_out: None,
+        junit: None,
+        baselines: BenchBaselines::new(),
+        out: Raw(Vec::new()),
+        use_color: false,
+        format: OutputFormat::Json,
+        report_time: None,
+        shuffle_seed: None,
+        total: 0,
+        passed: 0,
+        failed: 0,
+        ignored: 0,
+        measured: 0,
+        max_name_len: 10,
+        metrics: MetricMap::new(),
+        failures: Vec::new(),
+    };
+
+    st.write_result(&test, &TrFailed, Duration::from_millis(1500), b"line 1\nline 2")
+      .unwrap();
+    let s = match st.out {
+        Raw(ref m) => String::from_utf8_lossy(&m[..]),
+        Pretty(_) => unreachable!(),
+    };
+
+    assert_eq!(s,
+               "{ \"type\": \"test\", \"event\": \"failed\", \"name\": \"a::\\\"quoted\\\"\", \
+                \"exec_time\": 1.500000000, \"stdout\": \"line 1\\nline 2\" }\n");
+}
+
 fn use_color(opts: &TestOpts) -> bool {
     match opts.color {
         AutoColor => !opts.nocapture && stdout_isatty(),
@@ -856,7 +1753,7 @@ fn stdout_isatty() -> bool {
 enum TestEvent {
     TeFiltered(Vec<TestDesc>),
     TeWait(TestDesc, NamePadding),
-    TeResult(TestDesc, TestResult, Vec<u8>),
+    TeResult(TestDesc, TestResult, Vec<u8>, Duration),
     TeTimeout(TestDesc),
 }
 
@@ -866,7 +1763,7 @@ pub type MonitorMsg = (TestDesc, TestResult, Vec<u8>);
 fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F) -> io::Result<()>
     where F: FnMut(TestEvent) -> io::Result<()>
 {
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
     use std::sync::mpsc::RecvTimeoutError;
 
     let mut filtered_tests = filter_tests(opts, tests);
@@ -880,7 +1777,7 @@ fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F) ->
 
     callback(TeFiltered(filtered_descs))?;
 
-    let (filtered_tests, filtered_benchs_and_metrics): (Vec<_>, _) =
+    let (mut filtered_tests, filtered_benchs_and_metrics): (Vec<_>, _) =
         filtered_tests.into_iter().partition(|e| {
             match e.testfn {
                 StaticTestFn(_) | DynTestFn(_) => true,
@@ -888,6 +1785,10 @@ fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F) ->
             }
         });
 
+    if let Some(seed) = opts.shuffle_seed {
+        shuffle_tests(seed, &mut filtered_tests);
+    }
+
     let concurrency = match opts.test_threads {
         Some(n) => n,
         None => get_concurrency(),
@@ -899,24 +1800,56 @@ fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F) ->
 
     let (tx, rx) = channel::<MonitorMsg>();
 
-    let mut running_tests: HashMap<TestDesc, Instant> = HashMap::new();
+    // Tests that have been started but have not reported a result yet,
+    // along with when they started and when to warn about and fail them.
+    struct RunningTest {
+        start: Instant,
+        warn_at: Option<Instant>,
+        deadline: Option<Instant>,
+    }
 
-    fn get_timed_out_tests(running_tests: &mut HashMap<TestDesc, Instant>) -> Vec<TestDesc> {
+    let mut running_tests: HashMap<TestDesc, RunningTest> = HashMap::new();
+
+    // Tests that have been failed for exceeding their timeout. There's no
+    // way to stop a thread, so they are left running and whatever they
+    // eventually report is dropped.
+    let mut abandoned_tests: HashSet<TestDesc> = HashSet::new();
+
+    fn get_timed_out_tests(running_tests: &mut HashMap<TestDesc, RunningTest>) -> Vec<TestDesc> {
         let now = Instant::now();
-        let timed_out = running_tests.iter()
-            .filter_map(|(desc, timeout)| if &now >= timeout { Some(desc.clone())} else { None })
-            .collect();
-        for test in &timed_out {
-            running_tests.remove(test);
+        let mut timed_out = Vec::new();
+        for (desc, running) in running_tests.iter_mut() {
+            if running.warn_at.map_or(false, |warn_at| now >= warn_at) {
+                running.warn_at = None;
+                timed_out.push(desc.clone());
+            }
         }
         timed_out
     };
 
-    fn calc_timeout(running_tests: &HashMap<TestDesc, Instant>) -> Option<Duration> {
-        running_tests.values().min().map(|next_timeout| {
+    fn get_expired_tests(running_tests: &mut HashMap<TestDesc, RunningTest>)
+                         -> Vec<(TestDesc, Duration)> {
+        let now = Instant::now();
+        let expired: Vec<TestDesc> = running_tests.iter()
+            .filter(|&(_, running)| running.deadline.map_or(false, |deadline| now >= deadline))
+            .map(|(desc, _)| desc.clone())
+            .collect();
+        expired.into_iter()
+               .map(|desc| {
+                   let running = running_tests.remove(&desc).unwrap();
+                   (desc, now - running.start)
+               })
+               .collect()
+    };
+
+    fn calc_timeout(running_tests: &HashMap<TestDesc, RunningTest>) -> Option<Duration> {
+        running_tests.values()
+                     .flat_map(|running| running.warn_at.into_iter().chain(running.deadline))
+                     .min()
+                     .map(|next_timeout| {
             let now = Instant::now();
-            if *next_timeout >= now {
-                *next_timeout - now
+            if next_timeout >= now {
+                next_timeout - now
             } else {
                 Duration::new(0, 0)
             }})
@@ -931,35 +1864,62 @@ fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F) ->
                 // that hang forever.
                 callback(TeWait(test.desc.clone(), test.testfn.padding()))?;
             }
-            let timeout = Instant::now() + Duration::from_secs(TEST_WARN_TIMEOUT_S);
-            running_tests.insert(test.desc.clone(), timeout);
+            let now = Instant::now();
+            // Isolated tests enforce their own timeout, as they can kill
+            // the process running the test instead of abandoning it.
+            let timeout = if opts.isolate {
+                None
+            } else {
+                test.desc.timeout.or(opts.test_timeout)
+            };
+            running_tests.insert(test.desc.clone(),
+                                 RunningTest {
+                                     start: now,
+                                     warn_at: Some(now + Duration::from_secs(TEST_WARN_TIMEOUT_S)),
+                                     deadline: timeout.map(|secs| now + Duration::from_secs(secs)),
+                                 });
             run_test(opts, !opts.run_tests, test, tx.clone());
             pending += 1;
         }
 
-        let mut res;
-        loop {
-            if let Some(timeout) = calc_timeout(&running_tests) {
-                res = rx.recv_timeout(timeout);
-                for test in get_timed_out_tests(&mut running_tests) {
-                    callback(TeTimeout(test))?;
-                }
-                if res != Err(RecvTimeoutError::Timeout) {
-                    break;
-                }
-            } else {
-                res = rx.recv().map_err(|_| RecvTimeoutError::Disconnected);
-                break;
+        let res = match calc_timeout(&running_tests) {
+            Some(timeout) => rx.recv_timeout(timeout),
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        for test in get_timed_out_tests(&mut running_tests) {
+            callback(TeTimeout(test))?;
+        }
+
+        for (desc, exec_time) in get_expired_tests(&mut running_tests) {
+            let msg = format!("test {} did not finish within its timeout of {} seconds\n",
+                              desc.name,
+                              desc.timeout.or(opts.test_timeout).unwrap_or(0));
+            abandoned_tests.insert(desc.clone());
+            if concurrency != 1 {
+                callback(TeWait(desc.clone(), PadNone))?;
             }
+            callback(TeResult(desc, TrFailed, msg.into_bytes(), exec_time))?;
+            pending -= 1;
         }
 
-        let (desc, result, stdout) = res.unwrap();
-        running_tests.remove(&desc);
+        let (desc, result, stdout) = match res {
+            Ok(msg) => msg,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => panic!("test result channel disconnected"),
+        };
+        if abandoned_tests.remove(&desc) {
+            // Already reported as failed when its timeout expired.
+            continue;
+        }
+        let exec_time = running_tests.remove(&desc)
+                                     .map(|running| running.start.elapsed())
+                                     .unwrap_or(Duration::new(0, 0));
 
         if concurrency != 1 {
             callback(TeWait(desc.clone(), PadNone))?;
         }
-        callback(TeResult(desc, result, stdout))?;
+        callback(TeResult(desc, result, stdout, exec_time))?;
         pending -= 1;
     }
 
@@ -968,9 +1928,10 @@ fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F) ->
         // (this includes metric fns)
         for b in filtered_benchs_and_metrics {
             callback(TeWait(b.desc.clone(), b.testfn.padding()))?;
+            let start = Instant::now();
             run_test(opts, false, b, tx.clone());
             let (test, result, stdout) = rx.recv().unwrap();
-            callback(TeResult(test, result, stdout))?;
+            callback(TeResult(test, result, stdout, start.elapsed()))?;
         }
     }
     Ok(())
@@ -1091,16 +2052,23 @@ fn get_concurrency() -> usize {
 pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescAndFn> {
     let mut filtered = tests;
 
-    // Remove tests that don't match the test filter
-    filtered = match opts.filter {
-        None => filtered,
-        Some(ref filter) => {
-            filtered.into_iter()
-                    .filter(|test| test.desc.name.as_slice().contains(&filter[..]))
-                    .collect()
+    let matches_filter = |test: &TestDescAndFn, filter: &str| {
+        let name = test.desc.name.as_slice();
+        if opts.filter_exact {
+            name == filter
+        } else {
+            name.contains(filter)
         }
     };
 
+    // Remove tests that don't match any of the test filters
+    if !opts.filters.is_empty() {
+        filtered.retain(|test| opts.filters.iter().any(|filter| matches_filter(test, &filter[..])));
+    }
+
+    // Remove tests that match any of the skip filters
+    filtered.retain(|test| !opts.skip.iter().any(|sf| matches_filter(test, &sf[..])));
+
     // Maybe pull out the ignored test and unignore them
     filtered = if !opts.run_ignored {
         filtered
@@ -1122,9 +2090,44 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
     // Sort the tests alphabetically
     filtered.sort_by(|t1, t2| t1.desc.name.as_slice().cmp(t2.desc.name.as_slice()));
 
+    // Keep every `count`th test for the shard. This is done on the sorted
+    // list so that every shard gets the same tests each time.
+    if let Some((index, count)) = opts.shard {
+        filtered = filtered.into_iter()
+                           .enumerate()
+                           .filter(|&(i, _)| i % count == index - 1)
+                           .map(|(_, test)| test)
+                           .collect();
+    }
+
     filtered
 }
 
+// A small random number generator (SplitMix64). The order `--shuffle`
+// produces for a given seed has to be the same on every platform and in
+// every release, so it can't depend on anything outside of this crate.
+struct ShuffleRng(u64);
+
+impl ShuffleRng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Shuffles the tests into an order determined by `seed` (a Fisher-Yates
+/// shuffle).
+pub fn shuffle_tests(seed: u64, tests: &mut [TestDescAndFn]) {
+    let mut rng = ShuffleRng(seed);
+    for i in (1..tests.len()).rev() {
+        let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+        tests.swap(i, j);
+    }
+}
+
 pub fn convert_benchmarks_to_tests(tests: Vec<TestDescAndFn>) -> Vec<TestDescAndFn> {
     // convert benchmarks to tests, if we're not benchmarking them
     tests.into_iter()
@@ -1190,7 +2193,9 @@ pub fn run_test(opts: &TestOpts,
                                   .unwrap();
             let test_result = calc_result(&desc, result_guard.join());
             let stdout = data.lock().unwrap().to_vec();
-            monitor_ch.send((desc.clone(), test_result, stdout)).unwrap();
+            // The runner may have given up on this test because it timed
+            // out, in which case nobody is listening any more.
+            let _ = monitor_ch.send((desc.clone(), test_result, stdout));
         });
     }
 
@@ -1217,11 +2222,191 @@ pub fn run_test(opts: &TestOpts,
             monitor_ch.send((desc, TrMetrics(mm), Vec::new())).unwrap();
             return;
         }
+        DynTestFn(_) | StaticTestFn(_) if opts.isolate => {
+            let timeout = desc.timeout.or(opts.test_timeout);
+            run_test_isolated(desc, monitor_ch, opts.nocapture, timeout)
+        }
         DynTestFn(f) => run_test_inner(desc, monitor_ch, opts.nocapture, f),
         StaticTestFn(f) => run_test_inner(desc, monitor_ch, opts.nocapture, Box::new(f)),
     }
 }
 
+// Runs a test in a child process (see `run_isolated_child`), killing the
+// process if it outlives `timeout` seconds.
+fn run_test_isolated(desc: TestDesc,
+                     monitor_ch: Sender<MonitorMsg>,
+                     nocapture: bool,
+                     timeout: Option<u64>) {
+    use std::process::{Command, Output, Stdio};
+    use std::sync::mpsc::RecvTimeoutError;
+
+    fn spawn_child(desc: &TestDesc, nocapture: bool)
+                   -> io::Result<(u32, Receiver<io::Result<Output>>)> {
+        let args = env::args_os().skip(1).collect::<Vec<_>>();
+        let mut cmd = Command::new(env::current_exe()?);
+        cmd.args(&args)
+           .env(ISOLATED_TEST_ENV, desc.name.as_slice())
+           .stdin(Stdio::null());
+        if nocapture {
+            cmd.stdout(Stdio::inherit()).stderr(Stdio::inherit());
+        } else {
+            cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        }
+        let child = cmd.spawn()?;
+        let pid = child.id();
+
+        let (tx, rx) = channel();
+        thread::spawn(move || {
+            let _ = tx.send(child.wait_with_output());
+        });
+        Ok((pid, rx))
+    }
+
+    fn combined_output(output: Output) -> Vec<u8> {
+        let mut combined = output.stdout;
+        combined.extend_from_slice(&output.stderr);
+        combined
+    }
+
+    thread::spawn(move || {
+        let (pid, rx) = match spawn_child(&desc, nocapture) {
+            Ok(child) => child,
+            Err(e) => {
+                let msg = format!("failed to spawn the process for test {}: {}\n", desc.name, e);
+                let _ = monitor_ch.send((desc, TrFailed, msg.into_bytes()));
+                return;
+            }
+        };
+
+        let output = match timeout {
+            Some(secs) => rx.recv_timeout(Duration::from_secs(secs)),
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        let (result, stdout) = match output {
+            Ok(Ok(output)) => {
+                let status = output.status;
+                calc_isolated_result(&desc, status, combined_output(output))
+            }
+            Ok(Err(_)) | Err(RecvTimeoutError::Disconnected) => {
+                let msg = format!("failed to wait for the process of test {}\n", desc.name);
+                (TrFailed, msg.into_bytes())
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                kill_process(pid);
+                let mut stdout = match rx.recv() {
+                    Ok(Ok(output)) => combined_output(output),
+                    _ => Vec::new(),
+                };
+                stdout.extend_from_slice(format!("test {} did not finish within its timeout of \
+                                                  {} seconds and was killed\n",
+                                                 desc.name,
+                                                 timeout.unwrap_or(0))
+                                             .as_bytes());
+                (TrFailed, stdout)
+            }
+        };
+        let _ = monitor_ch.send((desc, result, stdout));
+    });
+}
+
+// Works out the result of a test run by `run_test_isolated` from the way
+// its process exited.
+fn calc_isolated_result(desc: &TestDesc,
+                        status: process::ExitStatus,
+                        mut stdout: Vec<u8>)
+                        -> (TestResult, Vec<u8>) {
+    if status.success() {
+        return (TrOk, stdout);
+    }
+    if status.code() == Some(ISOLATED_TEST_FAILED) {
+        return (TrFailed, stdout);
+    }
+
+    // The process died without reporting a result. If it printed a panic
+    // message first, this was a panic in a `panic=abort` build, which is
+    // all a `should_panic` test is asking for.
+    let panicked = {
+        let output = String::from_utf8_lossy(&stdout);
+        match desc.should_panic {
+            ShouldPanic::No => false,
+            ShouldPanic::Yes => output.contains("panicked at"),
+            ShouldPanic::YesWithMessage(msg) => {
+                output.contains("panicked at") && output.contains(msg)
+            }
+        }
+    };
+    if panicked {
+        return (TrOk, stdout);
+    }
+    stdout.extend_from_slice(format!("test process terminated abnormally ({})\n", status)
+                                 .as_bytes());
+    (TrFailed, stdout)
+}
+
+#[cfg(unix)]
+fn kill_process(pid: u32) {
+    unsafe {
+        libc::kill(pid as libc::pid_t, libc::SIGKILL);
+    }
+}
+#[cfg(windows)]
+fn kill_process(pid: u32) {
+    type DWORD = u32;
+    type BOOL = i32;
+    type HANDLE = *mut u8;
+    const PROCESS_TERMINATE: DWORD = 0x0001;
+    extern "system" {
+        fn OpenProcess(dwDesiredAccess: DWORD, bInheritHandle: BOOL, dwProcessId: DWORD)
+                       -> HANDLE;
+        fn TerminateProcess(hProcess: HANDLE, uExitCode: u32) -> BOOL;
+        fn CloseHandle(hObject: HANDLE) -> BOOL;
+    }
+    unsafe {
+        let handle = OpenProcess(PROCESS_TERMINATE, 0, pid);
+        if !handle.is_null() {
+            TerminateProcess(handle, 1);
+            CloseHandle(handle);
+        }
+    }
+}
+
+// The other half of `--isolate`: runs the single test that the parent
+// process asked for and exits with its result.
+fn run_isolated_child(tests: Vec<TestDescAndFn>, name: &str) -> ! {
+    // Tests which spawn test binaries of their own shouldn't pass this on.
+    env::remove_var(ISOLATED_TEST_ENV);
+
+    let test = tests.into_iter().find(|t| t.desc.name.as_slice() == name);
+    let TestDescAndFn { desc, testfn } = match test {
+        Some(test) => test,
+        None => {
+            let _ = writeln!(io::stderr(), "error: no test named `{}`", name);
+            process::exit(ISOLATED_TEST_FAILED);
+        }
+    };
+    let testfn: Box<FnBox() + Send> = match testfn {
+        StaticTestFn(f) => Box::new(f),
+        DynTestFn(f) => f,
+        StaticBenchFn(benchfn) => Box::new(move || bench::run_once(|b| benchfn(b))),
+        DynBenchFn(bench) => Box::new(move || bench::run_once(|b| bench.run(b))),
+        StaticMetricFn(..) | DynMetricFn(..) => {
+            let _ = writeln!(io::stderr(), "error: `{}` is not a test", name);
+            process::exit(ISOLATED_TEST_FAILED);
+        }
+    };
+
+    let result = thread::Builder::new()
+                     .name(name.to_owned())
+                     .spawn(move || testfn())
+                     .unwrap()
+                     .join();
+    if calc_result(&desc, result) == TrOk {
+        process::exit(0);
+    } else {
+        process::exit(ISOLATED_TEST_FAILED);
+    }
+}
+
 fn calc_result(desc: &TestDesc, task_result: Result<(), Box<Any + Send>>) -> TestResult {
     match (&desc.should_panic, task_result) {
         (&ShouldPanic::No, Ok(())) |
@@ -1270,6 +2455,21 @@ impl MetricMap {
                                .collect();
         v.join(", ")
     }
+
+    /// Formats the metrics as a JSON object mapping each name to its value
+    /// and noise.
+    pub fn fmt_metrics_json(&self) -> String {
+        let MetricMap(ref mm) = *self;
+        let v: Vec<String> = mm.iter()
+                               .map(|(k, v)| {
+                                   format!("\"{}\": {{ \"value\": {}, \"noise\": {} }}",
+                                           json_escape(k),
+                                           json_f64(v.value),
+                                           json_f64(v.noise))
+                               })
+                               .collect();
+        format!("{{ {} }}", v.join(", "))
+    }
 }
 
 
@@ -1353,7 +2553,7 @@ impl Bencher {
         }
 
         let mut total_run = Duration::new(0, 0);
-        let samples: &mut [f64] = &mut [0.0_f64; 50];
+        let samples: &mut [f64] = &mut [0.0_f64; BENCH_SAMPLE_COUNT];
         loop {
             let loop_start = Instant::now();
 
@@ -1439,8 +2639,16 @@ pub mod bench {
 #[cfg(test)]
 mod tests {
     use test::{TrFailed, TrIgnored, TrOk, filter_tests, parse_opts, TestDesc, TestDescAndFn,
-               TestOpts, run_test, MetricMap, StaticTestName, DynTestName, DynTestFn, ShouldPanic};
+               TestOpts, run_test, MetricMap, StaticTestName, DynTestName, DynTestFn, ShouldPanic,
+               OutputFormat};
+    use {calc_isolated_result, fmt_junit_report, run_tests, shuffle_tests, TimeThresholds};
+    use {compare_to_baseline, fmt_bench_baselines, parse_bench_baselines, BenchBaseline,
+         BENCH_BASELINE_HEADER};
+    use std::collections::BTreeMap;
+    use TestEvent::TeResult;
     use std::sync::mpsc::channel;
+    use std::thread;
+    use std::time::Duration;
 
     #[test]
     pub fn do_not_run_ignored_tests() {
@@ -1452,6 +2660,7 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: true,
                 should_panic: ShouldPanic::No,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move || f())),
         };
@@ -1469,6 +2678,7 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: true,
                 should_panic: ShouldPanic::No,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move || f())),
         };
@@ -1488,6 +2698,7 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: false,
                 should_panic: ShouldPanic::Yes,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move || f())),
         };
@@ -1507,6 +2718,7 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: false,
                 should_panic: ShouldPanic::YesWithMessage("error message"),
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move || f())),
         };
@@ -1526,6 +2738,7 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: false,
                 should_panic: ShouldPanic::YesWithMessage("foobar"),
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move || f())),
         };
@@ -1543,6 +2756,7 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: false,
                 should_panic: ShouldPanic::Yes,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move || f())),
         };
@@ -1562,6 +2776,132 @@ mod tests {
         assert!((opts.run_ignored));
     }
 
+    #[test]
+    fn parse_format_flag() {
+        let args = vec!["progname".to_string(), "--format".to_string(), "json".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_format_flag"),
+        };
+        assert_eq!(opts.format, OutputFormat::Json);
+
+        let args = vec!["progname".to_string(), "-q".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_format_flag"),
+        };
+        assert_eq!(opts.format, OutputFormat::Terse);
+
+        let args = vec!["progname".to_string(), "--format".to_string(), "xml".to_string()];
+        assert!(parse_opts(&args).unwrap().is_err());
+    }
+
+    #[test]
+    fn parse_timeout_flags() {
+        let args = vec!["progname".to_string(),
+                        "--test-timeout".to_string(),
+                        "30".to_string(),
+                        "--time-thresholds".to_string(),
+                        "100,200".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_timeout_flags"),
+        };
+        assert_eq!(opts.test_timeout, Some(30));
+        assert_eq!(opts.report_time,
+                   Some(TimeThresholds::new(Duration::from_millis(100),
+                                            Duration::from_millis(200))));
+
+        let args = vec!["progname".to_string(), "--report-time".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_timeout_flags"),
+        };
+        assert_eq!(opts.test_timeout, None);
+        assert_eq!(opts.report_time, Some(TimeThresholds::default()));
+
+        let args = vec!["progname".to_string(),
+                        "--time-thresholds".to_string(),
+                        "200,100".to_string()];
+        assert!(parse_opts(&args).unwrap().is_err());
+    }
+
+    #[test]
+    fn hung_test_fails_after_timeout() {
+        let mut opts = TestOpts::new();
+        opts.run_tests = true;
+        opts.test_timeout = Some(1);
+
+        let tests = vec![TestDescAndFn {
+                             desc: TestDesc {
+                                 name: StaticTestName("hangs"),
+                                 ignore: false,
+                                 should_panic: ShouldPanic::No,
+                                 timeout: None,
+                             },
+                             testfn: DynTestFn(Box::new(move || {
+                                 thread::sleep(Duration::from_secs(30))
+                             })),
+                         }];
+
+        let mut results = Vec::new();
+        run_tests(&opts, tests, |event| {
+            if let TeResult(desc, result, _, exec_time) = event {
+                results.push((desc.name.to_string(), result, exec_time));
+            }
+            Ok(())
+        }).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "hangs");
+        assert!(results[0].1 == TrFailed);
+        assert!(results[0].2 >= Duration::from_secs(1));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn isolated_test_results() {
+        use std::os::unix::process::ExitStatusExt;
+        use std::process::ExitStatus;
+
+        let desc = |should_panic| {
+            TestDesc {
+                name: StaticTestName("whatever"),
+                ignore: false,
+                should_panic: should_panic,
+                timeout: None,
+            }
+        };
+        let exited = |code: i32| ExitStatus::from_raw(code << 8);
+        let signaled = |signal: i32| ExitStatus::from_raw(signal);
+        let panic_output = b"thread 'whatever' panicked at 'an error message'\n".to_vec();
+
+        let (res, _) = calc_isolated_result(&desc(ShouldPanic::No), exited(0), Vec::new());
+        assert!(res == TrOk);
+        let (res, _) = calc_isolated_result(&desc(ShouldPanic::No), exited(101), Vec::new());
+        assert!(res == TrFailed);
+
+        // A panic in a panic=abort build
+        let (res, _) = calc_isolated_result(&desc(ShouldPanic::Yes),
+                                            signaled(6),
+                                            panic_output.clone());
+        assert!(res == TrOk);
+        let (res, _) = calc_isolated_result(&desc(ShouldPanic::YesWithMessage("error")),
+                                            signaled(6),
+                                            panic_output.clone());
+        assert!(res == TrOk);
+        let (res, _) = calc_isolated_result(&desc(ShouldPanic::YesWithMessage("foobar")),
+                                            signaled(6),
+                                            panic_output.clone());
+        assert!(res == TrFailed);
+        let (res, _) = calc_isolated_result(&desc(ShouldPanic::No), signaled(6), panic_output);
+        assert!(res == TrFailed);
+
+        let (res, out) = calc_isolated_result(&desc(ShouldPanic::Yes), signaled(11), Vec::new());
+        assert!(res == TrFailed);
+        assert!(String::from_utf8(out).unwrap().contains("terminated abnormally"));
+    }
+
     #[test]
     pub fn filter_for_ignored_option() {
         // When we run ignored tests the test filter should filter out all the
@@ -1576,6 +2916,7 @@ mod tests {
                                  name: StaticTestName("1"),
                                  ignore: true,
                                  should_panic: ShouldPanic::No,
+                                 timeout: None,
                              },
                              testfn: DynTestFn(Box::new(move || {})),
                          },
@@ -1584,6 +2925,7 @@ mod tests {
                                  name: StaticTestName("2"),
                                  ignore: false,
                                  should_panic: ShouldPanic::No,
+                                 timeout: None,
                              },
                              testfn: DynTestFn(Box::new(move || {})),
                          }];
@@ -1594,6 +2936,146 @@ mod tests {
         assert!(!filtered[0].desc.ignore);
     }
 
+    fn named_tests(names: &[&'static str]) -> Vec<TestDescAndFn> {
+        names.iter()
+             .map(|&name| {
+                 TestDescAndFn {
+                     desc: TestDesc {
+                         name: StaticTestName(name),
+                         ignore: false,
+                         should_panic: ShouldPanic::No,
+                         timeout: None,
+                     },
+                     testfn: DynTestFn(Box::new(move || {})),
+                 }
+             })
+             .collect()
+    }
+
+    fn filtered_names(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<String> {
+        filter_tests(opts, tests).into_iter().map(|t| t.desc.name.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_filter_flags() {
+        let args = vec!["progname".to_string(),
+                        "foo".to_string(),
+                        "bar".to_string(),
+                        "--exact".to_string(),
+                        "--skip".to_string(),
+                        "baz".to_string(),
+                        "--skip".to_string(),
+                        "qux".to_string(),
+                        "--list".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_filter_flags"),
+        };
+        assert_eq!(opts.filters, vec!["foo".to_string(), "bar".to_string()]);
+        assert!(opts.filter_exact);
+        assert_eq!(opts.skip, vec!["baz".to_string(), "qux".to_string()]);
+        assert!(opts.list);
+    }
+
+    #[test]
+    fn exact_filter_match() {
+        let names = ["base", "base::test", "base::test1", "base::test2"];
+
+        let mut opts = TestOpts::new();
+        opts.filters = vec!["base::test".to_string()];
+        assert_eq!(filtered_names(&opts, named_tests(&names)),
+                   vec!["base::test", "base::test1", "base::test2"]);
+
+        opts.filter_exact = true;
+        assert_eq!(filtered_names(&opts, named_tests(&names)), vec!["base::test"]);
+
+        opts.filters = vec!["test".to_string()];
+        assert!(filtered_names(&opts, named_tests(&names)).is_empty());
+    }
+
+    #[test]
+    fn multiple_filters_and_skip() {
+        let names = ["a::one", "a::two", "b::one", "b::two", "c::one"];
+
+        let mut opts = TestOpts::new();
+        opts.filters = vec!["a::".to_string(), "b::".to_string()];
+        assert_eq!(filtered_names(&opts, named_tests(&names)),
+                   vec!["a::one", "a::two", "b::one", "b::two"]);
+
+        opts.skip = vec!["two".to_string()];
+        assert_eq!(filtered_names(&opts, named_tests(&names)), vec!["a::one", "b::one"]);
+
+        opts.filters = Vec::new();
+        opts.skip = vec!["a::one".to_string(), "b::".to_string()];
+        opts.filter_exact = true;
+        assert_eq!(filtered_names(&opts, named_tests(&names)),
+                   vec!["a::two", "b::one", "b::two", "c::one"]);
+    }
+
+    #[test]
+    fn parse_shuffle_and_shard_flags() {
+        let args = vec!["progname".to_string(),
+                        "--shuffle-seed".to_string(),
+                        "42".to_string(),
+                        "--shard".to_string(),
+                        "2/3".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_shuffle_and_shard_flags"),
+        };
+        assert_eq!(opts.shuffle_seed, Some(42));
+        assert_eq!(opts.shard, Some((2, 3)));
+
+        let args = vec!["progname".to_string(), "--shuffle".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_shuffle_and_shard_flags"),
+        };
+        assert!(opts.shuffle_seed.is_some());
+
+        for shard in &["0/3", "4/3", "3", "a/b"] {
+            let args = vec!["progname".to_string(), "--shard".to_string(), shard.to_string()];
+            assert!(parse_opts(&args).unwrap().is_err());
+        }
+    }
+
+    #[test]
+    fn shards_partition_tests() {
+        let names = ["a", "b", "c", "d", "e", "f", "g"];
+        let mut opts = TestOpts::new();
+
+        let mut all = Vec::new();
+        for index in 1..4 {
+            opts.shard = Some((index, 3));
+            let shard = filtered_names(&opts, named_tests(&names));
+            assert!(shard.len() == 2 || shard.len() == 3);
+            all.extend(shard);
+        }
+        all.sort();
+        assert_eq!(all, names);
+
+        opts.shard = Some((2, 3));
+        assert_eq!(filtered_names(&opts, named_tests(&names)), vec!["b", "e"]);
+    }
+
+    #[test]
+    fn shuffle_is_deterministic() {
+        let names = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"];
+        let shuffled = |seed| {
+            let mut tests = named_tests(&names);
+            shuffle_tests(seed, &mut tests);
+            tests.into_iter().map(|t| t.desc.name.to_string()).collect::<Vec<_>>()
+        };
+
+        let order = shuffled(1234);
+        assert_eq!(order, shuffled(1234));
+        assert!(order != names);
+
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(sorted, names);
+    }
+
     #[test]
     pub fn sort_tests() {
         let mut opts = TestOpts::new();
@@ -1617,6 +3099,7 @@ mod tests {
                         name: DynTestName((*name).clone()),
                         ignore: false,
                         should_panic: ShouldPanic::No,
+                        timeout: None,
                     },
                     testfn: DynTestFn(Box::new(testfn)),
                 };
@@ -1641,6 +3124,93 @@ mod tests {
         }
     }
 
+    #[test]
+    fn junit_report_contents() {
+        let desc = |name: &'static str| {
+            TestDesc {
+                name: StaticTestName(name),
+                ignore: false,
+                should_panic: ShouldPanic::No,
+                timeout: None,
+            }
+        };
+        let cases = vec![(desc("a::passes"), TrOk, Duration::from_millis(10), Vec::new()),
+                         (desc("a::fails"),
+                          TrFailed,
+                          Duration::from_millis(20),
+                          b"thread 'a::fails' panicked at '1 < 2', lib.rs:1\n".to_vec()),
+                         (desc("skipped"), TrIgnored, Duration::new(0, 0), Vec::new())];
+        let report = fmt_junit_report("suite", &cases, Duration::from_secs(1));
+
+        assert!(report.contains("<testsuite name=\"suite\" tests=\"3\" failures=\"1\" \
+                                 errors=\"0\" skipped=\"1\" time=\"1.000000000\">"));
+        assert!(report.contains("<testcase classname=\"a\" name=\"passes\" \
+                                 time=\"0.010000000\"/>"));
+        assert!(report.contains("<failure message=\"thread &apos;a::fails&apos; panicked at \
+                                 &apos;1 &lt; 2&apos;, lib.rs:1\" type=\"panic\">"));
+        assert!(report.contains("<testcase classname=\"suite\" name=\"skipped\" \
+                                 time=\"0.000000000\"><skipped/></testcase>"));
+    }
+
+    #[test]
+    fn bench_baseline_comparison() {
+        let old = BenchBaseline {
+            median: 1000.0,
+            median_abs_dev: 10.0,
+            samples: 50,
+        };
+
+        let same = compare_to_baseline(&old, &old);
+        assert_eq!(same.median_pct, 0.0);
+        assert!(same.lower_pct < 0.0 && same.upper_pct > 0.0);
+        assert!(!same.is_regression() && !same.is_improvement());
+
+        let slower = compare_to_baseline(&old, &BenchBaseline { median: 1100.0, ..old });
+        assert!((slower.median_pct - 10.0).abs() < 1e-9);
+        assert!(slower.lower_pct > 0.0 && slower.upper_pct > slower.median_pct);
+        assert!(slower.is_regression() && !slower.is_improvement());
+
+        let faster = compare_to_baseline(&old, &BenchBaseline { median: 900.0, ..old });
+        assert!(faster.is_improvement() && !faster.is_regression());
+
+        // Within the noise of the measurements
+        let noisy = BenchBaseline { median_abs_dev: 500.0, ..old };
+        let change = compare_to_baseline(&noisy, &BenchBaseline { median: 1100.0, ..noisy });
+        assert!(change.lower_pct < 0.0);
+        assert!(!change.is_regression());
+
+        // Significant, but too small to matter
+        let precise = BenchBaseline { median_abs_dev: 0.0, ..old };
+        let change = compare_to_baseline(&precise, &BenchBaseline { median: 1010.0, ..precise });
+        assert!(change.lower_pct > 0.0);
+        assert!(!change.is_regression());
+    }
+
+    #[test]
+    fn bench_baseline_round_trip() {
+        let mut baselines = BTreeMap::new();
+        baselines.insert("bench::one".to_string(),
+                         BenchBaseline {
+                             median: 1234.5,
+                             median_abs_dev: 6.75,
+                             samples: 50,
+                         });
+        baselines.insert("bench::two\twith a tab".to_string(),
+                         BenchBaseline {
+                             median: 0.0,
+                             median_abs_dev: 0.0,
+                             samples: 50,
+                         });
+
+        let saved = fmt_bench_baselines(&baselines);
+        assert_eq!(parse_bench_baselines(&saved), Ok(baselines));
+
+        assert!(parse_bench_baselines("1\t2\t3\tname\n").is_err());
+        let malformed = format!("{}\n1\t2\tname\n", BENCH_BASELINE_HEADER);
+        assert_eq!(parse_bench_baselines(&malformed),
+                   Err("malformed baseline on line 2".to_string()));
+    }
+
     #[test]
     pub fn test_metricmap_compare() {
         let mut m1 = MetricMap::new();
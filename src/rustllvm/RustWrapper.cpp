@@ -917,6 +917,7 @@ enum class LLVMRustDiagnosticKind {
     OptimizationRemarkAnalysisAliasing,
     OptimizationRemarkOther,
     OptimizationFailure,
+    PGOProfile,
 };
 
 static LLVMRustDiagnosticKind
@@ -942,6 +943,8 @@ to_rust(DiagnosticKind kind)
         return LLVMRustDiagnosticKind::OptimizationRemarkAnalysisFPCommute;
     case DK_OptimizationRemarkAnalysisAliasing:
         return LLVMRustDiagnosticKind::OptimizationRemarkAnalysisAliasing;
+    case DK_PGOProfile:
+        return LLVMRustDiagnosticKind::PGOProfile;
 #endif
     default:
 #if LLVM_VERSION_GE(3, 9)
@@ -0,0 +1,22 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// min-llvm-version 3.9
+// compile-flags: -C profile-generate=pgo-data -O
+
+#![crate_type = "lib"]
+
+// CHECK: @__profc_foo = {{.*}}global
+// CHECK-LABEL: define void @foo
+// CHECK: load {{.*}} @__profc_foo
+#[no_mangle]
+#[inline(never)]
+pub fn foo() {
+}
@@ -0,0 +1,26 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// check-stdout
+// error-pattern:test process terminated abnormally
+// compile-flags: --test
+// run-flags: --isolate
+// ignore-pretty: does not work well with `--test`
+// ignore-emscripten spawning processes is not supported
+
+// The aborting test only fails itself, the rest of the run carries on.
+
+#[test]
+fn test_aborts() {
+    std::process::abort();
+}
+
+#[test]
+fn test_passes() {}
@@ -0,0 +1,26 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![allow(dead_code)]
+
+pub struct Padded {
+    a: u8,
+    b: u32,
+}
+
+pub enum Enum {
+    Big(u32),
+    Small(u8),
+}
+
+fn main() {
+    let _p = Padded { a: 0, b: 0 };
+    let _e = Enum::Small(0);
+}
@@ -0,0 +1,33 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The crate's documentation.
+
+/// A documented struct.
+///
+/// ```
+/// let _ = foo::Documented;
+/// ```
+pub struct Documented;
+
+impl Documented {
+    pub fn method(&self) {}
+}
+
+pub fn undocumented() {}
+
+pub mod inner {
+    //! The inner module's documentation.
+
+    /// A documented function.
+    pub fn documented() {}
+}
+
+fn private() {}
@@ -0,0 +1,36 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A documented crate, linking to [Nowhere].
+
+#![deny(unknown_code_block_attributes)]
+#![warn(missing_item_docs)]
+
+/// ```should-panic
+/// panic!();
+/// ```
+pub fn misspelled() {}
+
+pub fn undocumented() {}
+
+///
+pub fn empty() {}
+
+#[allow(missing_item_docs)]
+pub fn documented_enough() {}
+
+/// ```rsut
+/// let x = 1;
+/// ```
+///
+/// ```text
+/// not rust
+/// ```
+pub fn unknown() {}
@@ -0,0 +1,28 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_type = "lib"]
+
+/// ```
+/// assert_eq!(broken::triple(1), 3);
+/// // only the merged binary is passed the index of the test to run
+/// assert_eq!(std::env::args().count(), 2);
+/// ```
+///
+/// ```
+/// let x: u32 = "not a number";
+/// ```
+///
+/// ```ignore
+/// let y: u32 = "ignored";
+/// ```
+pub fn triple(x: u32) -> u32 {
+    x * 3
+}
@@ -0,0 +1,37 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_type = "lib"]
+
+/// ```
+/// assert_eq!(foo::double(2), 4);
+/// ```
+///
+/// ```
+/// let x = foo::double(3);
+/// assert_eq!(x, 6);
+/// ```
+///
+/// ```no_run
+/// loop {}
+/// ```
+///
+/// ```should_panic
+/// assert_eq!(foo::double(1), 3);
+/// ```
+///
+/// ```
+/// fn main() {
+///     assert_eq!(foo::double(0), 0);
+/// }
+/// ```
+pub fn double(x: u32) -> u32 {
+    x * 2
+}
@@ -0,0 +1,20 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_name = "foo"]
+
+// The language string of a code block is read in full, so a `cfg` with spaces
+// in it doesn't turn the block into a non-Rust one when rendering.
+
+// @has foo/fn.foo.html '//pre[@class="rust rust-example-rendered"]' 'feature'
+/// ```cfg(feature = "bar")
+/// assert!(cfg!(feature = "bar"));
+/// ```
+pub fn foo() {}
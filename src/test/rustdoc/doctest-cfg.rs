@@ -0,0 +1,27 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:--test
+
+/// ```rust,cfg(feature = "bar"),cfg(baz)
+/// assert!(cfg!(feature = "bar"));
+/// assert!(cfg!(baz));
+/// ```
+///
+/// ```rust,cfg(all(qux, feature = "quux"))
+/// assert!(cfg!(qux));
+/// assert!(cfg!(feature = "quux"));
+/// assert!(!cfg!(baz));
+/// ```
+///
+/// ```rust
+/// assert!(!cfg!(feature = "bar"));
+/// ```
+pub fn foo() {}
@@ -0,0 +1,62 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// @has intra_links/index.html
+// @has - '//a[@href="../intra_links/struct.ThisType.html"]' 'ThisType'
+// @has - '//a[@href="../intra_links/enum.ThisEnum.html"]' 'ThisEnum'
+// @has - '//a[@href="../intra_links/trait.ThisTrait.html"]' 'ThisTrait'
+// @has - '//a[@href="../intra_links/fn.this_function.html"]' 'this_function'
+// @has - '//a[@href="../intra_links/constant.THIS_CONST.html"]' 'THIS_CONST'
+// @has - '//a[@href="../intra_links/static.THIS_STATIC.html"]' 'THIS_STATIC'
+// @has - '//a[@href="../intra_links/inner/struct.Inner.html"]' 'this inner struct'
+// @has - '//a[@href="../intra_links/struct.ThisType.html#method.this_method"]' \
+//      'ThisType::this_method'
+// @has - '//a[@href="../intra_links/enum.ThisEnum.html#variant.ThisVariant"]' \
+//      'ThisEnum::ThisVariant'
+// @has - '//a[@href="../intra_links/trait.ThisTrait.html#tymethod.this_required"]' \
+//      'ThisTrait::this_required'
+// @has - '//a[@href="../intra_links/struct.ThisType.html#structfield.this_field"]' \
+//      'ThisType::this_field'
+//! In this crate we would like to link you to:
+//!
+//! * [`ThisType`](ThisType)-style inline links are left alone, while
+//! * [ThisType], [ThisEnum], [ThisTrait], [`this_function`], [THIS_CONST]
+//!   and [THIS_STATIC] are resolved, as is [this inner struct][inner::Inner].
+//! * So are [`ThisType::this_method`], [ThisEnum::ThisVariant],
+//!   [ThisTrait::this_required] and [`ThisType::this_field`].
+
+pub mod inner {
+    // @has intra_links/inner/struct.Inner.html
+    // @has - '//a[@href="../../intra_links/struct.ThisType.html"]' 'super::ThisType'
+    // @has - '//a[@href="../../intra_links/struct.ThisType.html"]' 'crate::ThisType'
+    /// Links relative to the parent module work too: [super::ThisType] and
+    /// [crate::ThisType].
+    pub struct Inner;
+}
+
+pub struct ThisType {
+    pub this_field: u32,
+}
+
+impl ThisType {
+    pub fn this_method() {}
+}
+
+pub enum ThisEnum {
+    ThisVariant,
+}
+
+pub trait ThisTrait {
+    fn this_required(&self);
+}
+
+pub fn this_function() {}
+pub const THIS_CONST: usize = 5;
+pub static THIS_STATIC: usize = 5;
@@ -0,0 +1,32 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_name = "foo"]
+
+// @has search-doc/foo.js 'addSearchDocs("foo"'
+// @has - '"frobnicated":'
+// @!has - '"a":'
+// @!has foo/index.html 'search-index.js'
+
+/// Parses a frobnicated widget from a byte vector.
+// @has search-index.js '[[["vec",["u8"]]],"usize"]'
+// @has - '"w":"by fr pa ve wi"'
+pub fn parse(_bytes: &Vec<u8>) -> usize {
+    0
+}
+
+pub struct Widget;
+
+impl Widget {
+    // @has search-index.js '[["self"],"usize"]'
+    pub fn len(&self) -> usize {
+        0
+    }
+}
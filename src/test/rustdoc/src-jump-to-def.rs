@@ -0,0 +1,41 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:src-links-external.rs
+// build-aux-docs
+// ignore-cross-compile
+
+#![crate_name = "foo"]
+
+extern crate src_links_external;
+
+pub struct Bar;
+
+impl Bar {
+    pub fn method(&self) {}
+}
+
+pub fn make() -> Bar {
+    let bar = Bar;
+    bar.method();
+    bar
+}
+
+pub fn external(foo: src_links_external::Foo) -> src_links_external::Foo {
+    foo
+}
+
+// @has src/foo/src-jump-to-def.rs.html
+// @has - '//pre[@class="rust "]//a[@href="../../src/foo/src-jump-to-def.rs.html#19"]' 'Bar'
+// @has - '//pre[@class="rust "]//a[@href="../../src/foo/src-jump-to-def.rs.html#22"]' 'method'
+// @has - '//pre[@class="rust "]//a[@href="../../src_links_external/struct.Foo.html"]' 'Foo'
+// @has - '//div[@class="used-by"]//a[@href="#19"]' 'Bar'
+// @has - '//div[@class="used-by"]//a[@href="../../src/foo/src-jump-to-def.rs.html#27"]' \
+//      'src-jump-to-def.rs:27'
@@ -0,0 +1,67 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_name = "foo"]
+
+use std::cell::Cell;
+use std::fmt;
+
+// @has foo/struct.Plain.html
+// @has - '//*[@id="synthetic-implementations"]' 'Auto Trait Implementations'
+// @has - '//code' 'impl Send for Plain'
+// @has - '//code' 'impl Sync for Plain'
+pub struct Plain {
+    pub x: u32,
+}
+
+// @has foo/struct.Wrapper.html
+// @has - '//code' 'impl<T> Send for Wrapper<T> where T: Send'
+// @has - '//code' 'impl<T> Sync for Wrapper<T> where T: Sync'
+pub struct Wrapper<T> {
+    pub inner: T,
+}
+
+// @has foo/struct.Shared.html
+// @has - '//code' 'impl<T> Send for Shared<T> where T: Send'
+// @has - '//code' 'impl<T> !Sync for Shared<T>'
+pub struct Shared<T> {
+    pub cell: Cell<T>,
+}
+
+// @has foo/struct.Manual.html
+// @has - '//*[@id="implementations"]' 'Trait Implementations'
+// @has - '//code' 'impl Send for Manual'
+// @has - '//*[@id="synthetic-implementations"]' 'Auto Trait Implementations'
+// @has - '//code' 'impl !Sync for Manual'
+pub struct Manual {
+    pub p: *const u8,
+}
+
+unsafe impl Send for Manual {}
+
+// @has foo/struct.Shown.html
+// @has - '//*[@id="blanket-implementations"]' 'Blanket Implementations'
+// @has - '//code' 'impl<T> ToString for T where T: Display + ?Sized'
+// @has - '//code' 'impl<T> Local for T where T: Display'
+pub struct Shown;
+
+impl fmt::Display for Shown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "shown")
+    }
+}
+
+pub trait Local {}
+
+impl<T: fmt::Display> Local for T {}
+
+// @has foo/trait.Local.html
+// @!has - '//*[@id="implementors-list"]//code' 'Shown'
+// @has - '//*[@id="implementors-list"]//code' 'impl<T> Local for T'
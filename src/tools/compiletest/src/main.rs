@@ -301,10 +301,13 @@ pub fn run_tests(config: &Config) {
 
 pub fn test_opts(config: &Config) -> test::TestOpts {
     test::TestOpts {
-        filter: config.filter.clone(),
+        list: false,
+        filters: config.filter.iter().cloned().collect(),
+        filter_exact: false,
+        skip: Vec::new(),
         run_ignored: config.run_ignored,
-        quiet: config.quiet,
         logfile: config.logfile.clone(),
+        junit: None,
         run_tests: true,
         bench_benchmarks: true,
         nocapture: match env::var("RUST_TEST_NOCAPTURE") {
@@ -312,7 +315,15 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
             Err(_) => false
         },
         color: test::AutoColor,
+        format: if config.quiet { test::OutputFormat::Terse } else { test::OutputFormat::Pretty },
         test_threads: None,
+        test_timeout: None,
+        report_time: None,
+        isolate: false,
+        shuffle_seed: None,
+        shard: None,
+        baseline: None,
+        save_baseline: None,
     }
 }
 
@@ -435,6 +446,7 @@ pub fn make_test(config: &Config, testpaths: &TestPaths) -> test::TestDescAndFn
             name: make_test_name(config, testpaths),
             ignore: early_props.ignore,
             should_panic: should_panic,
+            timeout: None,
         },
         testfn: make_test_closure(config, testpaths),
     }